@@ -0,0 +1,215 @@
+use crate::maze::{pipe_directions, Direction, Maze, PipeLoop};
+use crate::MyCoord;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum JunkKind {
+    // No neighbour connects back
+    Isolated,
+    DeadEnd,
+    // Both ends connect, but the chain never closes into a loop
+    OpenChain,
+}
+
+#[derive(Debug)]
+pub struct MazeAnalysis {
+    // The loop through S comes first when there is one
+    pub loops: Vec<PipeLoop>,
+    pub junk: Vec<(MyCoord, JunkKind)>,
+    tiles: Vec<Vec<char>>,
+    loop_ids: Vec<Vec<Option<usize>>>,
+    // Loops enclosing a tile, outermost first
+    enclosing: Vec<Vec<Vec<usize>>>,
+}
+
+impl MazeAnalysis {
+    pub fn new(maze: &Maze) -> Self {
+        let start_loop = maze.find_loop().ok();
+        let mut tiles = maze.tiles.clone();
+        tiles[maze.start.y as usize][maze.start.x as usize] = start_loop
+            .as_ref()
+            .map_or('.', |pipe_loop| pipe_loop.start_tile);
+
+        let mut analysis = Self {
+            loops: Vec::new(),
+            junk: Vec::new(),
+            loop_ids: tiles.iter().map(|row| vec![None; row.len()]).collect(),
+            enclosing: Vec::new(),
+            tiles,
+        };
+        if let Some(pipe_loop) = start_loop {
+            analysis.add_loop(pipe_loop);
+        }
+        analysis.find_loops();
+        analysis.classify_junk();
+        analysis.find_enclosures();
+        analysis
+    }
+
+    fn tile(&self, coord: &MyCoord) -> Option<char> {
+        if coord.x < 0 || coord.y < 0 {
+            return None;
+        }
+        self.tiles
+            .get(coord.y as usize)
+            .and_then(|row| row.get(coord.x as usize))
+            .copied()
+    }
+
+    fn connects(&self, coord: &MyCoord, direction: Direction) -> bool {
+        self.tile(coord)
+            .is_some_and(|tile| pipe_directions(tile).contains(&direction))
+            && self
+                .tile(&coord.step(direction))
+                .is_some_and(|tile| pipe_directions(tile).contains(&direction.get_opposite()))
+    }
+
+    fn add_loop(&mut self, pipe_loop: PipeLoop) {
+        for tile in &pipe_loop.tiles {
+            self.loop_ids[tile.y as usize][tile.x as usize] = Some(self.loops.len());
+        }
+        self.loops.push(pipe_loop);
+    }
+
+    // Every pipe connects to at most two others, so a walk from a tile either closes
+    // back on it or runs into a dead end and none of the walked tiles are on a loop.
+    // Walks stop at tiles walked before, so every tile is walked once
+    fn find_loops(&mut self) {
+        let mut walked: Vec<Vec<bool>> = self
+            .loop_ids
+            .iter()
+            .map(|row| row.iter().map(|id| id.is_some()).collect())
+            .collect();
+        for y in 0..self.tiles.len() {
+            for x in 0..self.tiles[y].len() {
+                let start = MyCoord {
+                    x: x as i64,
+                    y: y as i64,
+                };
+                let directions = pipe_directions(self.tiles[y][x]);
+                if walked[y][x] || directions.is_empty() {
+                    continue;
+                }
+                let mut tiles = vec![start.clone()];
+                let mut coord = start.clone();
+                let mut heading = directions[0];
+                let closed = loop {
+                    if !self.connects(&coord, heading) {
+                        break false;
+                    }
+                    coord = coord.step(heading);
+                    if coord == start {
+                        break true;
+                    }
+                    // An earlier walk already found this tile to be on an open chain
+                    if walked[coord.y as usize][coord.x as usize] {
+                        break false;
+                    }
+                    let came_from = heading.get_opposite();
+                    heading = *pipe_directions(self.tile(&coord).unwrap())
+                        .iter()
+                        .find(|direction| **direction != came_from)
+                        .unwrap();
+                    tiles.push(coord.clone());
+                };
+                for tile in &tiles {
+                    walked[tile.y as usize][tile.x as usize] = true;
+                }
+                if closed {
+                    self.add_loop(PipeLoop {
+                        start_tile: self.tiles[y][x],
+                        tiles,
+                    });
+                }
+            }
+        }
+    }
+
+    fn classify_junk(&mut self) {
+        for y in 0..self.tiles.len() {
+            for x in 0..self.tiles[y].len() {
+                let coord = MyCoord {
+                    x: x as i64,
+                    y: y as i64,
+                };
+                if self.loop_ids[y][x].is_some() || pipe_directions(self.tiles[y][x]).is_empty() {
+                    continue;
+                }
+                let kind = match Direction::iterator()
+                    .filter(|direction| self.connects(&coord, **direction))
+                    .count()
+                {
+                    0 => JunkKind::Isolated,
+                    1 => JunkKind::DeadEnd,
+                    _ => JunkKind::OpenChain,
+                };
+                self.junk.push((coord, kind));
+            }
+        }
+    }
+
+    // The crossing parity scan of Maze::inside_mask, kept for all loops at once
+    fn find_enclosures(&mut self) {
+        for (row, row_ids) in self.tiles.iter().zip(self.loop_ids.iter()) {
+            let mut odd: Vec<usize> = Vec::new();
+            let mut row_enclosing = Vec::new();
+            for (tile, id) in row.iter().zip(row_ids.iter()) {
+                row_enclosing.push(
+                    odd.iter()
+                        .filter(|loop_id| Some(**loop_id) != *id)
+                        .copied()
+                        .collect::<Vec<usize>>(),
+                );
+                if let Some(loop_id) = id {
+                    if pipe_directions(*tile).contains(&Direction::North) {
+                        match odd.iter().position(|other| other == loop_id) {
+                            Some(position) => {
+                                odd.remove(position);
+                            }
+                            None => odd.push(*loop_id),
+                        }
+                    }
+                }
+            }
+            self.enclosing.push(row_enclosing);
+        }
+
+        let depths: Vec<usize> = self
+            .loops
+            .iter()
+            .map(|pipe_loop| self.enclosing_loops(&pipe_loop.tiles[0]).len())
+            .collect();
+        for row in self.enclosing.iter_mut() {
+            for tile_enclosing in row.iter_mut() {
+                tile_enclosing.sort_by_key(|loop_id| depths[*loop_id]);
+            }
+        }
+    }
+
+    pub fn loop_at(&self, coord: &MyCoord) -> Option<usize> {
+        self.tile(coord)?;
+        self.loop_ids[coord.y as usize][coord.x as usize]
+    }
+
+    pub fn enclosing_loops(&self, coord: &MyCoord) -> &[usize] {
+        if self.tile(coord).is_none() {
+            return &[];
+        }
+        &self.enclosing[coord.y as usize][coord.x as usize]
+    }
+
+    // Counts every tile inside the loop, including junk and the tiles of nested loops
+    pub fn enclosed_tile_count(&self, loop_id: usize) -> usize {
+        self.enclosing
+            .iter()
+            .flatten()
+            .filter(|tile_enclosing| tile_enclosing.contains(&loop_id))
+            .count()
+    }
+
+    // The innermost loop around the given one
+    pub fn parent(&self, loop_id: usize) -> Option<usize> {
+        self.enclosing_loops(&self.loops[loop_id].tiles[0])
+            .last()
+            .copied()
+    }
+}
@@ -0,0 +1,189 @@
+use std::slice::Iter;
+
+use self::Direction::*;
+use crate::MyCoord;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    West,
+    South,
+    East,
+}
+
+impl Direction {
+    pub fn iterator() -> Iter<'static, Direction> {
+        static DIRECTIONS: [Direction; 4] = [North, West, South, East];
+        DIRECTIONS.iter()
+    }
+
+    pub fn get_opposite(&self) -> Direction {
+        match self {
+            North => South,
+            West => East,
+            South => North,
+            East => West,
+        }
+    }
+}
+
+pub fn pipe_directions(pipe_char: char) -> &'static [Direction] {
+    match pipe_char {
+        '|' => &[North, South],
+        '-' => &[West, East],
+        'L' => &[North, East],
+        'J' => &[North, West],
+        '7' => &[West, South],
+        'F' => &[South, East],
+        _ => &[],
+    }
+}
+
+pub fn pipe_for_directions(first: Direction, second: Direction) -> Option<char> {
+    "|-LJ7F".chars().find(|pipe_char| {
+        let directions = pipe_directions(*pipe_char);
+        directions.contains(&first) && directions.contains(&second) && first != second
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipeLoop {
+    // The pipe under the first tile, which is S when the loop goes through it
+    pub start_tile: char,
+    // Every tile of the loop once, in walking order
+    pub tiles: Vec<MyCoord>,
+}
+
+#[derive(Debug)]
+pub struct Maze {
+    pub tiles: Vec<Vec<char>>,
+    pub start: MyCoord,
+}
+
+impl Maze {
+    pub fn parse(input: &str) -> Result<Self, &'static str> {
+        let tiles: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+        let mut start = None;
+        for (y, row) in tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                match tile {
+                    'S' if start.is_some() => return Err("Multiple start tiles"),
+                    'S' => {
+                        start = Some(MyCoord {
+                            x: x as i64,
+                            y: y as i64,
+                        })
+                    }
+                    '|' | '-' | 'L' | 'J' | '7' | 'F' | '.' => {}
+                    _ => return Err("Invalid char"),
+                }
+            }
+        }
+        Ok(Self {
+            tiles,
+            start: start.ok_or("No start tile")?,
+        })
+    }
+
+    pub fn tile(&self, coord: &MyCoord) -> Option<char> {
+        if coord.x < 0 || coord.y < 0 {
+            return None;
+        }
+        self.tiles
+            .get(coord.y as usize)
+            .and_then(|row| row.get(coord.x as usize))
+            .copied()
+    }
+
+    // Walks from S leaving towards `first` and succeeds if it comes back from `second`
+    fn trace_from(&self, first: Direction, second: Direction) -> Option<Vec<MyCoord>> {
+        let tile_count: usize = self.tiles.iter().map(|row| row.len()).sum();
+        let mut tiles = vec![self.start.clone()];
+        let mut coord = self.start.clone();
+        let mut heading = first;
+        loop {
+            coord = coord.step(heading);
+            if coord == self.start {
+                return (heading.get_opposite() == second).then_some(tiles);
+            }
+            let directions = pipe_directions(self.tile(&coord)?);
+            let came_from = heading.get_opposite();
+            if !directions.contains(&came_from) || tiles.len() >= tile_count {
+                return None;
+            }
+            heading = *directions.iter().find(|d| **d != came_from)?;
+            tiles.push(coord.clone());
+        }
+    }
+
+    // S hides the pipe that connects two of its neighbours pointing back at it into a loop
+    pub fn find_loop(&self) -> Result<PipeLoop, &'static str> {
+        let connected: Vec<Direction> = Direction::iterator()
+            .filter(|direction| {
+                self.tile(&self.start.step(**direction))
+                    .is_some_and(|tile| pipe_directions(tile).contains(&direction.get_opposite()))
+            })
+            .copied()
+            .collect();
+        for (index, first) in connected.iter().enumerate() {
+            for second in &connected[index + 1..] {
+                if let Some(tiles) = self.trace_from(*first, *second) {
+                    return Ok(PipeLoop {
+                        start_tile: pipe_for_directions(*first, *second).unwrap(),
+                        tiles,
+                    });
+                }
+            }
+        }
+        Err("The start tile is not part of a loop")
+    }
+
+    pub fn loop_mask(&self, pipe_loop: &PipeLoop) -> Vec<Vec<bool>> {
+        let mut mask: Vec<Vec<bool>> = self
+            .tiles
+            .iter()
+            .map(|row| vec![false; row.len()])
+            .collect();
+        for tile in &pipe_loop.tiles {
+            mask[tile.y as usize][tile.x as usize] = true;
+        }
+        mask
+    }
+
+    // Scanning a row from the left, every loop tile with a north connection is a crossing,
+    // so tiles after an odd number of crossings are inside
+    pub fn inside_mask(&self, pipe_loop: &PipeLoop) -> Vec<Vec<bool>> {
+        let on_loop = self.loop_mask(pipe_loop);
+        self.tiles
+            .iter()
+            .zip(on_loop.iter())
+            .map(|(row, row_on_loop)| {
+                let mut inside = false;
+                row.iter()
+                    .zip(row_on_loop.iter())
+                    .map(|(tile, is_loop)| {
+                        if !is_loop {
+                            return inside;
+                        }
+                        let tile = if *tile == 'S' {
+                            pipe_loop.start_tile
+                        } else {
+                            *tile
+                        };
+                        if pipe_directions(tile).contains(&North) {
+                            inside = !inside;
+                        }
+                        false
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn enclosed_tile_count(&self, pipe_loop: &PipeLoop) -> usize {
+        self.inside_mask(pipe_loop)
+            .iter()
+            .map(|row| row.iter().filter(|inside| **inside).count())
+            .sum()
+    }
+}
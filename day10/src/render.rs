@@ -0,0 +1,129 @@
+use crate::maze::{Maze, PipeLoop};
+
+const CELL_SIZE: usize = 12;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TileKind {
+    Loop,
+    Inside,
+    Outside,
+}
+
+pub fn box_glyph(tile: char) -> char {
+    match tile {
+        '|' => '│',
+        '-' => '─',
+        'L' => '└',
+        'J' => '┘',
+        '7' => '┐',
+        'F' => '┌',
+        '.' => '·',
+        _ => tile,
+    }
+}
+
+pub fn classify(maze: &Maze, pipe_loop: &PipeLoop) -> Vec<Vec<TileKind>> {
+    let on_loop = maze.loop_mask(pipe_loop);
+    let inside = maze.inside_mask(pipe_loop);
+    on_loop
+        .iter()
+        .zip(inside.iter())
+        .map(|(row_on_loop, row_inside)| {
+            row_on_loop
+                .iter()
+                .zip(row_inside.iter())
+                .map(|(is_loop, is_inside)| match (is_loop, is_inside) {
+                    (true, _) => TileKind::Loop,
+                    (false, true) => TileKind::Inside,
+                    (false, false) => TileKind::Outside,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// S is drawn as the pipe it hides so the loop reads as one unbroken line
+fn visible_tile(maze: &Maze, pipe_loop: &PipeLoop, x: usize, y: usize) -> char {
+    match maze.tiles[y][x] {
+        'S' => pipe_loop.start_tile,
+        tile => tile,
+    }
+}
+
+pub fn render_ansi(maze: &Maze, pipe_loop: &PipeLoop) -> String {
+    let mut output = String::new();
+    for (y, row) in classify(maze, pipe_loop).iter().enumerate() {
+        for (x, kind) in row.iter().enumerate() {
+            let glyph = box_glyph(visible_tile(maze, pipe_loop, x, y));
+            let style = match kind {
+                TileKind::Loop if maze.tiles[y][x] == 'S' => "1;31",
+                TileKind::Loop => "1;33",
+                TileKind::Inside => "30;42",
+                TileKind::Outside => "2;37",
+            };
+            output += &format!("\x1b[{}m{}\x1b[0m", style, glyph);
+        }
+        output += "\n";
+    }
+    output
+}
+
+pub fn render_svg(maze: &Maze, pipe_loop: &PipeLoop) -> String {
+    let kinds = classify(maze, pipe_loop);
+    let height = kinds.len() * CELL_SIZE;
+    let width = kinds.iter().map(|row| row.len()).max().unwrap_or(0) * CELL_SIZE;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"{}\">\n",
+        width, height, CELL_SIZE
+    );
+    for (y, row) in kinds.iter().enumerate() {
+        for (x, kind) in row.iter().enumerate() {
+            let fill = match kind {
+                TileKind::Loop => "#fff8e1",
+                TileKind::Inside => "#66bb6a",
+                TileKind::Outside => "#eeeeee",
+            };
+            svg += &format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                x * CELL_SIZE,
+                y * CELL_SIZE,
+                CELL_SIZE,
+                CELL_SIZE,
+                fill
+            );
+            if *kind != TileKind::Loop && maze.tiles[y][x] != '.' {
+                svg += &format!(
+                    "<text x=\"{}\" y=\"{}\" fill=\"#9e9e9e\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                    x * CELL_SIZE + CELL_SIZE / 2,
+                    y * CELL_SIZE + CELL_SIZE / 2,
+                    box_glyph(maze.tiles[y][x])
+                );
+            }
+        }
+    }
+    let points: Vec<String> = pipe_loop
+        .tiles
+        .iter()
+        .map(|tile| {
+            format!(
+                "{},{}",
+                tile.x as usize * CELL_SIZE + CELL_SIZE / 2,
+                tile.y as usize * CELL_SIZE + CELL_SIZE / 2
+            )
+        })
+        .collect();
+    svg += &format!(
+        "<polygon points=\"{}\" fill=\"none\" stroke=\"#e65100\" stroke-width=\"{}\" stroke-linejoin=\"round\"/>\n",
+        points.join(" "),
+        CELL_SIZE / 3
+    );
+    let start = &maze.start;
+    svg += &format!(
+        "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"#c62828\"/>\n",
+        start.x as usize * CELL_SIZE + CELL_SIZE / 2,
+        start.y as usize * CELL_SIZE + CELL_SIZE / 2,
+        CELL_SIZE / 3
+    );
+    svg += "</svg>\n";
+    svg
+}
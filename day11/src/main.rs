@@ -1,3 +1,7 @@
+mod queries;
+
+use crate::queries::Metric;
+
 const EXAMPLE: &str = include_str!("example.txt");
 const INPUT: &str = include_str!("input.txt");
 
@@ -24,53 +28,87 @@ impl SpaceCoord {
 }
 #[derive(Debug)]
 struct Universe {
-    galaxies: Vec<SpaceCoord>
+    galaxies: Vec<SpaceCoord>,
+    width: i64,
+    height: i64,
+}
+
+// Sum of |a - b| over all pairs, every value is counted positively by the pairs below it
+// and negatively by the pairs above it once sorted
+fn pairwise_distance_sum(mut values: Vec<i64>) -> i64 {
+    values.sort_unstable();
+    let mut sum = 0;
+    let mut prefix = 0;
+    for (index, value) in values.iter().enumerate() {
+        sum += value * index as i64 - prefix;
+        prefix += value;
+    }
+    sum
 }
 
 impl Universe {
     fn new(input: &str) -> Self {
         let mut galaxies = vec!{};
-        let mut empty_rows = vec!{};
-        let mut empty_cols = vec!{};
         for (line_index, line) in input.lines().enumerate() {
-            if !line.contains('#') {
-                empty_rows.push(line_index as i64);
-            }
             for (char_index, char) in line.chars().enumerate() {
                 if char == '#' {
                     galaxies.push(SpaceCoord::new(char_index as i64, line_index as i64));
                 }
             }
         }
-        let number_of_rows = input.lines().count() as i64;
-        for column in 0..number_of_rows {
-            if !galaxies.iter().any(|star_coord|{star_coord.x == column}) {
-                empty_cols.push(column);
-            }
-        }
+        let height = input.lines().count() as i64;
+        let width = input
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0) as i64;
 
-        for empty_row in &empty_rows {
-            galaxies.iter_mut().filter(|galaxy| {galaxy.y > *empty_row }).for_each(|galaxy|{galaxy.expanded_y += 1});
+        let mut occupied_rows = vec![false; height as usize];
+        let mut occupied_cols = vec![false; width as usize];
+        for galaxy in &galaxies {
+            occupied_rows[galaxy.y as usize] = true;
+            occupied_cols[galaxy.x as usize] = true;
         }
-        for empty_col in &empty_cols {
-            galaxies.iter_mut().filter(|galaxy| {galaxy.x > *empty_col }).for_each(|galaxy|{galaxy.expanded_x += 1});
+        // Number of empty rows or columns before each index
+        let empty_before = |occupied: &[bool]| {
+            let mut counts = Vec::with_capacity(occupied.len());
+            let mut count = 0;
+            for is_occupied in occupied {
+                counts.push(count);
+                if !is_occupied {
+                    count += 1;
+                }
+            }
+            counts
+        };
+        let empty_rows_before = empty_before(&occupied_rows);
+        let empty_cols_before = empty_before(&occupied_cols);
+        for galaxy in galaxies.iter_mut() {
+            galaxy.expanded_y = empty_rows_before[galaxy.y as usize];
+            galaxy.expanded_x = empty_cols_before[galaxy.x as usize];
         }
 
-        return Universe{galaxies};
+        Universe {
+            galaxies,
+            width,
+            height,
+        }
     }
     fn score(&self, expansion_multiplier: i64) -> i64 {
-        let mut sum = 0;
-        for galaxy_coord in &self.galaxies {
-            for galaxy_coord_2 in &self.galaxies {
-                if galaxy_coord != galaxy_coord_2 {
-                    let x_diff = galaxy_coord_2.sum_x(expansion_multiplier) - galaxy_coord.sum_x(expansion_multiplier);
-                    let y_diff = galaxy_coord_2.sum_y(expansion_multiplier) - galaxy_coord.sum_y(expansion_multiplier);
-                    sum += x_diff.abs();
-                    sum += y_diff.abs();
-                }
-            }
-        }
-        return sum/2;
+        self.score_per_axis(expansion_multiplier, expansion_multiplier)
+    }
+
+    // Empty columns widen the x axis and empty rows the y axis
+    fn score_per_axis(&self, column_multiplier: i64, row_multiplier: i64) -> i64 {
+        let xs = self
+            .galaxies
+            .iter()
+            .map(|galaxy| galaxy.sum_x(column_multiplier));
+        let ys = self
+            .galaxies
+            .iter()
+            .map(|galaxy| galaxy.sum_y(row_multiplier));
+        pairwise_distance_sum(xs.collect()) + pairwise_distance_sum(ys.collect())
     }
 }
 
@@ -81,6 +119,51 @@ fn main() {
     assert_eq!(example_universe.score(10), 1030);
     assert_eq!(example_universe.score(100), 8410);
 
+    assert_eq!(example_universe.score_per_axis(1, 1), 292);
+    assert_eq!(example_universe.score_per_axis(2, 10), 662);
+    assert_eq!(example_universe.score_per_axis(10, 2), 742);
+
+    // Wider than tall, the column at index 3 is empty too
+    let wide_universe = Universe::new("#...#\n.....\n..#..");
+    assert_eq!((wide_universe.width, wide_universe.height), (5, 3));
+    assert_eq!(wide_universe.score(2), 18);
+    assert_eq!(wide_universe.score_per_axis(3, 1), 20);
+
+    assert_eq!(example_universe.galaxy_count(), 9);
+    assert_eq!(
+        example_universe.distance(5, 9, 2, Metric::Manhattan),
+        Some(9)
+    );
+    assert_eq!(
+        example_universe.distance(1, 7, 2, Metric::Manhattan),
+        Some(15)
+    );
+    assert_eq!(
+        example_universe.distance(3, 6, 2, Metric::Manhattan),
+        Some(17)
+    );
+    assert_eq!(
+        example_universe.distance(8, 9, 2, Metric::Manhattan),
+        Some(5)
+    );
+    assert_eq!(example_universe.distance(0, 9, 2, Metric::Manhattan), None);
+    assert_eq!(
+        example_universe.nearest(5, 3, 2, Metric::Manhattan),
+        vec![(3, 5), (8, 6), (4, 8)]
+    );
+    assert_eq!(
+        example_universe.nearest(5, 3, 100, Metric::Chebyshev),
+        vec![(3, 102), (8, 103), (9, 103)]
+    );
+    assert_eq!(
+        example_universe.farthest_pair(2, Metric::Manhattan),
+        Some((2, 8, 19))
+    );
+    assert_eq!(
+        example_universe.farthest_pair(100, Metric::Chebyshev),
+        Some((3, 6, 306))
+    );
+
     let input_universe = Universe::new(INPUT);
     assert_eq!(input_universe.score(2), 9556896);
     assert_eq!(input_universe.score(1000000), 685038186836);
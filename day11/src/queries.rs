@@ -0,0 +1,105 @@
+use crate::Universe;
+
+type Projection = fn(&(i64, i64)) -> i64;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Metric {
+    Manhattan,
+    Chebyshev,
+}
+
+impl Metric {
+    pub fn distance(&self, a: (i64, i64), b: (i64, i64)) -> i64 {
+        let x_diff = (a.0 - b.0).abs();
+        let y_diff = (a.1 - b.1).abs();
+        match self {
+            Metric::Manhattan => x_diff + y_diff,
+            Metric::Chebyshev => x_diff.max(y_diff),
+        }
+    }
+}
+
+// Galaxies are numbered from 1 in reading order, like in the puzzle description
+impl Universe {
+    pub fn galaxy_count(&self) -> usize {
+        self.galaxies.len()
+    }
+
+    fn position(&self, galaxy: usize, expansion_multiplier: i64) -> Option<(i64, i64)> {
+        let coord = self.galaxies.get(galaxy.checked_sub(1)?)?;
+        Some((
+            coord.sum_x(expansion_multiplier),
+            coord.sum_y(expansion_multiplier),
+        ))
+    }
+
+    pub fn distance(
+        &self,
+        first: usize,
+        second: usize,
+        expansion_multiplier: i64,
+        metric: Metric,
+    ) -> Option<i64> {
+        Some(metric.distance(
+            self.position(first, expansion_multiplier)?,
+            self.position(second, expansion_multiplier)?,
+        ))
+    }
+
+    // Closest first, ties broken by galaxy number
+    pub fn nearest(
+        &self,
+        galaxy: usize,
+        k: usize,
+        expansion_multiplier: i64,
+        metric: Metric,
+    ) -> Vec<(usize, i64)> {
+        let Some(origin) = self.position(galaxy, expansion_multiplier) else {
+            return vec![];
+        };
+        let mut others: Vec<(usize, i64)> = (1..=self.galaxy_count())
+            .filter(|other| *other != galaxy)
+            .map(|other| {
+                let position = self.position(other, expansion_multiplier).unwrap();
+                (other, metric.distance(origin, position))
+            })
+            .collect();
+        others.sort_by_key(|(other, distance)| (*distance, *other));
+        others.truncate(k);
+        others
+    }
+
+    // Manhattan distance is the Chebyshev distance after rotating by 45 degrees, so in both
+    // metrics the farthest pair sits at the extremes of two projections
+    pub fn farthest_pair(
+        &self,
+        expansion_multiplier: i64,
+        metric: Metric,
+    ) -> Option<(usize, usize, i64)> {
+        if self.galaxy_count() < 2 {
+            return None;
+        }
+        let positions: Vec<(i64, i64)> = (1..=self.galaxy_count())
+            .map(|galaxy| self.position(galaxy, expansion_multiplier).unwrap())
+            .collect();
+        let projections: [Projection; 2] = match metric {
+            Metric::Manhattan => [|(x, y)| x + y, |(x, y)| x - y],
+            Metric::Chebyshev => [|(x, _)| *x, |(_, y)| *y],
+        };
+        projections
+            .iter()
+            .map(|projection| {
+                let extreme = |sign: i64| {
+                    (1..=positions.len())
+                        .min_by_key(|galaxy| sign * projection(&positions[galaxy - 1]))
+                        .unwrap()
+                };
+                let (low, high) = (extreme(1), extreme(-1));
+                let distance = metric.distance(positions[low - 1], positions[high - 1]);
+                (low.min(high), low.max(high), distance)
+            })
+            .max_by_key(|(first, second, distance)| {
+                (*distance, std::cmp::Reverse((*first, *second)))
+            })
+    }
+}
@@ -0,0 +1,222 @@
+use rand::Rng;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellCounts {
+    pub total: u128,
+    // Number of arrangements in which each cell is '#'
+    pub broken: Vec<u128>,
+}
+
+impl CellCounts {
+    // The value every arrangement agrees on, nothing is forced when there is no arrangement
+    pub fn forced(&self) -> Vec<Option<u8>> {
+        self.broken
+            .iter()
+            .map(|broken| match broken {
+                _ if self.total == 0 => None,
+                0 => Some(b'.'),
+                broken if *broken == self.total => Some(b'#'),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn broken_ratios(&self) -> Vec<f64> {
+        self.broken
+            .iter()
+            .map(|broken| match self.total {
+                0 => 0.0,
+                total => *broken as f64 / total as f64,
+            })
+            .collect()
+    }
+}
+
+pub struct ArrangementCounts {
+    pattern: Vec<u8>,
+    springs: Vec<usize>,
+    // Prefix counts of '.' to check in O(1) that a block fits
+    operational: Vec<usize>,
+    // ways[placed][position] completes springs[placed..] within pattern[position..]
+    ways: Vec<Vec<u128>>,
+}
+
+impl ArrangementCounts {
+    pub fn new(pattern: &[u8], springs: &[usize]) -> Self {
+        let mut operational = vec![0; pattern.len() + 1];
+        for (i, &b) in pattern.iter().enumerate() {
+            operational[i + 1] = operational[i] + usize::from(b == b'.');
+        }
+        let mut counts = Self {
+            pattern: pattern.to_vec(),
+            springs: springs.to_vec(),
+            operational,
+            ways: vec![vec![0; pattern.len() + 2]; springs.len() + 1],
+        };
+        counts.ways[springs.len()][pattern.len()] = 1;
+        counts.ways[springs.len()][pattern.len() + 1] = 1;
+        for placed in (0..=springs.len()).rev() {
+            for position in (0..pattern.len()).rev() {
+                counts.ways[placed][position] = counts.operational_branch(placed, position)
+                    + counts.broken_branch(placed, position);
+            }
+        }
+        counts
+    }
+
+    pub fn total(&self) -> u128 {
+        self.ways[0][0]
+    }
+
+    fn block_fits(&self, position: usize, size: usize) -> bool {
+        let end = position + size;
+        end <= self.pattern.len()
+            && self.operational[end] == self.operational[position]
+            && self.pattern.get(end) != Some(&b'#')
+    }
+
+    // Arrangements where the cell at `position` is '.'
+    fn operational_branch(&self, placed: usize, position: usize) -> u128 {
+        if self.pattern[position] == b'#' {
+            return 0;
+        }
+        self.ways[placed][position + 1]
+    }
+
+    // Arrangements where the next block starts at `position`
+    fn broken_branch(&self, placed: usize, position: usize) -> u128 {
+        match self.springs.get(placed) {
+            Some(&size) if self.block_fits(position, size) => {
+                self.ways[placed + 1][(position + size + 1).min(self.pattern.len() + 1)]
+            }
+            _ => 0,
+        }
+    }
+
+    // forward[placed][position] fills pattern[..position] with springs[..placed] so that
+    // a block may start at position, the mirror image of ways
+    fn forward(&self) -> Vec<Vec<u128>> {
+        let mut forward = vec![vec![0; self.pattern.len() + 2]; self.springs.len() + 1];
+        forward[0][0] = 1;
+        for position in 0..self.pattern.len() {
+            for placed in 0..=self.springs.len() {
+                let count = forward[placed][position];
+                if count == 0 {
+                    continue;
+                }
+                if self.pattern[position] != b'#' {
+                    forward[placed][position + 1] += count;
+                }
+                if let Some(&size) = self.springs.get(placed) {
+                    if self.block_fits(position, size) {
+                        forward[placed + 1][(position + size + 1).min(self.pattern.len() + 1)] +=
+                            count;
+                    }
+                }
+            }
+        }
+        forward
+    }
+
+    // Every block placement is counted by the arrangements before it times the ones after it
+    // and covers its cells, the covered ranges are summed with a sweep over starts and ends
+    pub fn cell_counts(&self) -> CellCounts {
+        let forward = self.forward();
+        let mut starts = vec![0; self.pattern.len() + 1];
+        let mut ends = vec![0; self.pattern.len() + 1];
+        for (placed, &size) in self.springs.iter().enumerate() {
+            for position in 0..self.pattern.len() {
+                if forward[placed][position] == 0 || !self.block_fits(position, size) {
+                    continue;
+                }
+                let after =
+                    self.ways[placed + 1][(position + size + 1).min(self.pattern.len() + 1)];
+                let count = forward[placed][position] * after;
+                starts[position] += count;
+                ends[position + size] += count;
+            }
+        }
+        let mut broken = Vec::with_capacity(self.pattern.len());
+        let mut covering = 0;
+        for position in 0..self.pattern.len() {
+            covering -= ends[position];
+            covering += starts[position];
+            broken.push(covering);
+        }
+        CellCounts {
+            total: self.total(),
+            broken,
+        }
+    }
+
+    fn place_block(&self, arrangement: &mut Vec<u8>, placed: usize) -> usize {
+        let size = self.springs[placed];
+        arrangement.extend(std::iter::repeat_n(b'#', size));
+        if arrangement.len() < self.pattern.len() {
+            arrangement.push(b'.');
+        }
+        arrangement.len()
+    }
+
+    pub fn arrangements(&self) -> Arrangements<'_> {
+        Arrangements {
+            counts: self,
+            stack: if self.total() > 0 {
+                vec![(0, 0, Vec::new())]
+            } else {
+                vec![]
+            },
+        }
+    }
+
+    // Walks the table from the start choosing each branch with probability proportional
+    // to the number of arrangements behind it
+    pub fn sample(&self, rng: &mut impl Rng) -> Option<Vec<u8>> {
+        if self.total() == 0 {
+            return None;
+        }
+        let mut arrangement = Vec::with_capacity(self.pattern.len());
+        let mut placed = 0;
+        while arrangement.len() < self.pattern.len() {
+            let position = arrangement.len();
+            let operational = self.operational_branch(placed, position);
+            let broken = self.broken_branch(placed, position);
+            if rng.gen_range(0..operational + broken) < broken {
+                self.place_block(&mut arrangement, placed);
+                placed += 1;
+            } else {
+                arrangement.push(b'.');
+            }
+        }
+        Some(arrangement)
+    }
+}
+
+// Depth first over the table, branches without arrangements are never entered
+// so every step is on the way to the next arrangement
+pub struct Arrangements<'a> {
+    counts: &'a ArrangementCounts,
+    stack: Vec<(usize, usize, Vec<u8>)>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((placed, position, mut arrangement)) = self.stack.pop() {
+            if position >= self.counts.pattern.len() {
+                return Some(arrangement);
+            }
+            if self.counts.operational_branch(placed, position) > 0 {
+                let mut operational = arrangement.clone();
+                operational.push(b'.');
+                self.stack.push((placed, position + 1, operational));
+            }
+            if self.counts.broken_branch(placed, position) > 0 {
+                let next_position = self.counts.place_block(&mut arrangement, placed);
+                self.stack.push((placed + 1, next_position, arrangement));
+            }
+        }
+        None
+    }
+}
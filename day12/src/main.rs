@@ -1,3 +1,10 @@
+mod arrangements;
+mod nonogram;
+
+use crate::arrangements::ArrangementCounts;
+use crate::nonogram::{render, Nonogram, Solutions};
+use rand::SeedableRng;
+
 type Input<'a> = Vec<(&'a [u8], Vec<usize>)>;
 
 pub fn parse(input: &str) -> Input<'_> {
@@ -12,95 +19,312 @@ pub fn parse(input: &str) -> Input<'_> {
         .collect()
 }
 
-pub fn part1(input: &Input<'_>) -> u64 {
+pub fn part1(input: &Input<'_>) -> u128 {
     solve(input, 1)
 }
 
-pub fn part2(input: &Input<'_>) -> u64 {
+pub fn part2(input: &Input<'_>) -> u128 {
     solve(input, 5)
 }
 
-pub fn solve(input: &Input<'_>, repeat: usize) -> u64 {
-    let mut result = 0;
-    let mut pattern = Vec::new();
-    let mut springs = Vec::new();
-    let mut broken = vec![0; 200];
-    let mut table = vec![0; 200 * 50];
-
-    for (first, second) in input {
-        pattern.clear();
-        springs.clear();
-
-        for _ in 1..repeat {
-            pattern.extend_from_slice(first);
-            pattern.push(b'?');
-            springs.extend_from_slice(second);
-        }
+// `repeat` copies of the row joined by '?'
+pub fn unfold(first: &[u8], second: &[usize], repeat: usize) -> (Vec<u8>, Vec<usize>) {
+    let pattern = vec![first; repeat].join(&b'?');
+    let springs = second.repeat(repeat);
+    (pattern, springs)
+}
 
-        pattern.extend_from_slice(first);
-        pattern.push(b'.');
-        springs.extend_from_slice(second);
+pub fn solve(input: &Input<'_>, repeat: usize) -> u128 {
+    input
+        .iter()
+        .map(|(first, second)| {
+            let (pattern, springs) = unfold(first, second, repeat);
+            count_arrangements(&pattern, &springs)
+        })
+        .sum()
+}
 
-        let mut sum = 0;
-        broken.push(0);
+pub fn count_arrangements(row: &[u8], springs: &[usize]) -> u128 {
+    if springs.is_empty() {
+        return u128::from(!row.contains(&b'#'));
+    }
 
-        for (i, &b) in pattern.iter().enumerate() {
-            if b != b'.' {
-                sum += 1;
-            }
-            broken[i + 1] = sum;
-        }
+    let mut pattern = row.to_vec();
+    pattern.push(b'.');
 
-        let wiggle = pattern.len() - springs.iter().sum::<usize>() - springs.len() + 1;
+    let needed = springs.iter().sum::<usize>() + springs.len();
+    if needed > pattern.len() {
+        return 0;
+    }
+    let wiggle = pattern.len() - needed + 1;
 
-        let size = springs[0];
-        let mut sum = 0;
-        let mut valid = true;
+    let mut broken = vec![0; pattern.len() + 1];
+    let mut sum = 0;
 
-        for i in 0..wiggle {
-            if pattern[i + size] == b'#' {
-                sum = 0;
-            } else if valid && broken[i + size] - broken[i] == size {
-                sum += 1;
-            }
+    for (i, &b) in pattern.iter().enumerate() {
+        if b != b'.' {
+            sum += 1;
+        }
+        broken[i + 1] = sum;
+    }
+
+    let mut table = vec![0; springs.len() * pattern.len()];
 
-            table[i + size] = sum;
+    let size = springs[0];
+    let mut sum = 0;
+    let mut valid = true;
 
-            valid &= pattern[i] != b'#';
+    for i in 0..wiggle {
+        if pattern[i + size] == b'#' {
+            sum = 0;
+        } else if valid && broken[i + size] - broken[i] == size {
+            sum += 1;
         }
 
-        let mut start = size + 1;
+        table[i + size] = sum;
 
-        for (row, &size) in springs.iter().enumerate().skip(1) {
-            let previous = (row - 1) * pattern.len();
-            let current = row * pattern.len();
+        valid &= pattern[i] != b'#';
+    }
 
-            sum = 0;
+    let mut start = size + 1;
 
-            for i in start..start + wiggle {
-                if pattern[i + size] == b'#' {
-                    sum = 0;
-                } else if table[previous + i - 1] > 0
-                    && pattern[i - 1] != b'#'
-                    && broken[i + size] - broken[i] == size
-                {
-                    sum += table[previous + i - 1];
-                }
+    for (row, &size) in springs.iter().enumerate().skip(1) {
+        let previous = (row - 1) * pattern.len();
+        let current = row * pattern.len();
 
-                table[current + i + size] = sum;
+        sum = 0;
+
+        for i in start..start + wiggle {
+            if pattern[i + size] == b'#' {
+                sum = 0;
+            } else if table[previous + i - 1] > 0
+                && pattern[i - 1] != b'#'
+                && broken[i + size] - broken[i] == size
+            {
+                sum += table[previous + i - 1];
             }
 
-            start += size + 1;
+            table[current + i + size] = sum;
         }
 
-        result += sum;
+        start += size + 1;
     }
 
-    result
+    sum
 }
 
 fn main()  {
     const EXAMPLE: &str = include_str!("example.txt");
     const INPUT: &str = include_str!("input.txt");
+    assert_eq!(part1(&parse(EXAMPLE)), 21);
+    assert_eq!(part2(&parse(EXAMPLE)), 525152);
+
+    let (pattern, springs) = unfold(b"?###????????", &[3, 2, 1], 2);
+    let counts = ArrangementCounts::new(&pattern, &springs);
+    assert_eq!(counts.total(), count_arrangements(&pattern, &springs));
+    for arrangement in counts.arrangements().take(3) {
+        println!("{}", String::from_utf8_lossy(&arrangement));
+    }
+    for (first, second) in parse(EXAMPLE) {
+        let cell_counts = ArrangementCounts::new(first, &second).cell_counts();
+        let forced: String = cell_counts
+            .forced()
+            .iter()
+            .map(|cell| cell.map_or('?', char::from))
+            .collect();
+        println!(
+            "{} -> {} ({} arrangements)",
+            String::from_utf8_lossy(first),
+            forced,
+            cell_counts.total
+        );
+    }
+    let cell_counts = ArrangementCounts::new(b"?###????????", &[3, 2, 1]).cell_counts();
+    assert_eq!(cell_counts.total, 10);
+    assert_eq!(
+        cell_counts.forced()[..5],
+        [Some(b'.'), Some(b'#'), Some(b'#'), Some(b'#'), Some(b'.')]
+    );
+    assert_eq!(cell_counts.broken_ratios()[5], 0.4);
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(12);
+    let (pattern, springs) = unfold(b"?###????????", &[3, 2, 1], 5);
+    let sample = ArrangementCounts::new(&pattern, &springs)
+        .sample(&mut rng)
+        .unwrap();
+    println!("{}", String::from_utf8_lossy(&sample));
+
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(path) = args.iter().skip_while(|arg| *arg != "--nonogram").nth(1) {
+        let clues = std::fs::read_to_string(path).expect("Error reading the clue file");
+        match Nonogram::parse(&clues).unwrap().solve() {
+            Solutions::Unsolvable => println!("No solution"),
+            Solutions::Unique(solution) => println!("Unique solution\n{}", render(&solution)),
+            Solutions::Multiple(first, second) => println!(
+                "Multiple solutions, for example\n{}\n\n{}",
+                render(&first),
+                render(&second)
+            ),
+        }
+    }
+
     println!("{}", part2(&parse(INPUT)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arrangements::CellCounts;
+    use std::collections::HashMap;
+
+    // Straightforward memoised recursion over (position, springs placed)
+    fn reference(pattern: &[u8], springs: &[usize]) -> u128 {
+        fn go(
+            pattern: &[u8],
+            springs: &[usize],
+            position: usize,
+            placed: usize,
+            memo: &mut HashMap<(usize, usize), u128>,
+        ) -> u128 {
+            if placed == springs.len() {
+                return u128::from(!pattern[position.min(pattern.len())..].contains(&b'#'));
+            }
+            if position >= pattern.len() {
+                return 0;
+            }
+            if let Some(count) = memo.get(&(position, placed)) {
+                return *count;
+            }
+            let mut count = 0;
+            if pattern[position] != b'#' {
+                count += go(pattern, springs, position + 1, placed, memo);
+            }
+            let end = position + springs[placed];
+            if end <= pattern.len()
+                && !pattern[position..end].contains(&b'.')
+                && pattern.get(end) != Some(&b'#')
+            {
+                count += go(pattern, springs, end + 1, placed + 1, memo);
+            }
+            memo.insert((position, placed), count);
+            count
+        }
+        go(pattern, springs, 0, 0, &mut HashMap::new())
+    }
+
+    #[test]
+    fn test_large_unfold_factors() {
+        let input = parse(include_str!("example.txt"));
+        for repeat in [6, 10, 20, 25] {
+            for (first, second) in &input {
+                let (pattern, springs) = unfold(first, second, repeat);
+                let count = count_arrangements(&pattern, &springs);
+                assert_eq!(count, reference(&pattern, &springs));
+                assert_eq!(ArrangementCounts::new(&pattern, &springs).total(), count);
+            }
+        }
+        // ?###???????? 3,2,1 unfolded 25 times is longer than 300 cells
+        assert!(solve(&input, 25) > u64::MAX as u128);
+    }
+
+    fn is_arrangement_of(arrangement: &[u8], pattern: &[u8], springs: &[usize]) -> bool {
+        arrangement.len() == pattern.len()
+            && arrangement
+                .iter()
+                .zip(pattern)
+                .all(|(a, p)| *p == b'?' || a == p)
+            && count_arrangements(arrangement, springs) == 1
+            && !arrangement.contains(&b'?')
+    }
+
+    #[test]
+    fn test_arrangements() {
+        for (first, second) in parse(include_str!("example.txt")) {
+            let (pattern, springs) = unfold(first, &second, 2);
+            let counts = ArrangementCounts::new(&pattern, &springs);
+            assert_eq!(counts.total(), count_arrangements(&pattern, &springs));
+            let arrangements: Vec<Vec<u8>> = counts.arrangements().collect();
+            assert_eq!(arrangements.len() as u128, counts.total());
+            assert!(arrangements.iter().all(|arrangement| is_arrangement_of(
+                arrangement,
+                &pattern,
+                &springs
+            )));
+            let mut distinct = arrangements.clone();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(distinct.len(), arrangements.len());
+        }
+
+        // The iterator only walks as far as it is asked to
+        let (pattern, springs) = unfold(b"?###????????", &[3, 2, 1], 20);
+        let counts = ArrangementCounts::new(&pattern, &springs);
+        assert!(counts
+            .arrangements()
+            .take(100)
+            .all(|arrangement| is_arrangement_of(&arrangement, &pattern, &springs)));
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(2023);
+        let counts = ArrangementCounts::new(b".??..??...?##.", &[1, 1, 3]);
+        let mut seen: HashMap<Vec<u8>, usize> = HashMap::new();
+        for _ in 0..4000 {
+            *seen.entry(counts.sample(&mut rng).unwrap()).or_default() += 1;
+        }
+        assert_eq!(seen.len(), 4);
+        assert!(seen.values().all(|hits| (800..1200).contains(hits)));
+
+        let (pattern, springs) = unfold(b"?###????????", &[3, 2, 1], 25);
+        let sample = ArrangementCounts::new(&pattern, &springs)
+            .sample(&mut rng)
+            .unwrap();
+        assert!(is_arrangement_of(&sample, &pattern, &springs));
+        assert_eq!(ArrangementCounts::new(b"#.#", &[2]).sample(&mut rng), None);
+    }
+
+    #[test]
+    fn test_cell_counts() {
+        for (first, second) in parse(include_str!("example.txt")) {
+            let (pattern, springs) = unfold(first, &second, 2);
+            let counts = ArrangementCounts::new(&pattern, &springs);
+            let mut broken = vec![0; pattern.len()];
+            for arrangement in counts.arrangements() {
+                for (cell, b) in arrangement.iter().enumerate() {
+                    broken[cell] += u128::from(*b == b'#');
+                }
+            }
+            assert_eq!(
+                counts.cell_counts(),
+                CellCounts {
+                    total: counts.total(),
+                    broken
+                }
+            );
+        }
+
+        let impossible = ArrangementCounts::new(b"#.#", &[2]).cell_counts();
+        assert_eq!(impossible.total, 0);
+        assert_eq!(impossible.forced(), vec![None; 3]);
+
+        // Huge unfolds only need the two tables
+        let (pattern, springs) = unfold(b"?###????????", &[3, 2, 1], 25);
+        let forced = ArrangementCounts::new(&pattern, &springs)
+            .cell_counts()
+            .forced();
+        assert_eq!(
+            forced[..5],
+            [Some(b'.'), Some(b'#'), Some(b'#'), Some(b'#'), Some(b'.')]
+        );
+    }
+
+    #[test]
+    fn test_edge_rows() {
+        assert_eq!(count_arrangements(b"???", &[]), 1);
+        assert_eq!(count_arrangements(b"?#?", &[]), 0);
+        assert_eq!(count_arrangements(b"???", &[2, 2]), 0);
+        assert_eq!(count_arrangements(b"", &[]), 1);
+        assert_eq!(unfold(b"#.", &[1], 0), (vec![], vec![]));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,216 @@
+use std::collections::VecDeque;
+
+use crate::arrangements::ArrangementCounts;
+
+pub type Grid = Vec<Vec<u8>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Solutions {
+    Unsolvable,
+    Unique(Grid),
+    // Two of the solutions as witnesses
+    Multiple(Grid, Grid),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Line {
+    Row(usize),
+    Column(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nonogram {
+    pub rows: Vec<Vec<usize>>,
+    pub columns: Vec<Vec<usize>>,
+}
+
+fn parse_clue(line: &str) -> Result<Vec<usize>, &'static str> {
+    let clue = line
+        .trim()
+        .split(',')
+        .map(|s| s.trim().parse::<usize>().map_err(|_| "Invalid clue"))
+        .collect::<Result<Vec<usize>, &'static str>>()?;
+    // A lone 0 is an empty line
+    Ok(clue.into_iter().filter(|size| *size > 0).collect())
+}
+
+pub fn render(grid: &Grid) -> String {
+    grid.iter()
+        .map(|row| String::from_utf8_lossy(row).into_owned())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+impl Nonogram {
+    // A "rows" line followed by one clue per row like "1,1,3", then the same for "columns"
+    pub fn parse(input: &str) -> Result<Self, &'static str> {
+        let mut rows = Vec::new();
+        let mut columns = Vec::new();
+        let mut section = None;
+        for line in input.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            match line {
+                "rows" => section = Some(&mut rows),
+                "columns" => section = Some(&mut columns),
+                clue => section
+                    .as_mut()
+                    .ok_or("Clue outside of a section")?
+                    .push(parse_clue(clue)?),
+            }
+        }
+        if rows.is_empty() || columns.is_empty() {
+            return Err("Missing rows or columns");
+        }
+        Ok(Self { rows, columns })
+    }
+
+    fn line_cells(&self, grid: &Grid, line: Line) -> Vec<u8> {
+        match line {
+            Line::Row(row) => grid[row].clone(),
+            Line::Column(column) => grid.iter().map(|row| row[column]).collect(),
+        }
+    }
+
+    // Fixes every cell that is the same in all arrangements of the line and queues
+    // the crossing lines of the changed cells
+    fn solve_line(
+        &self,
+        grid: &mut Grid,
+        line: Line,
+        queue: &mut VecDeque<Line>,
+    ) -> Result<(), &'static str> {
+        let clue = match line {
+            Line::Row(row) => &self.rows[row],
+            Line::Column(column) => &self.columns[column],
+        };
+        let cell_counts = ArrangementCounts::new(&self.line_cells(grid, line), clue).cell_counts();
+        if cell_counts.total == 0 {
+            return Err("Contradiction");
+        }
+        for (index, forced) in cell_counts.forced().into_iter().enumerate() {
+            let (row, column, crossing) = match line {
+                Line::Row(row) => (row, index, Line::Column(index)),
+                Line::Column(column) => (index, column, Line::Row(index)),
+            };
+            if let Some(value) = forced {
+                if grid[row][column] == b'?' {
+                    grid[row][column] = value;
+                    if !queue.contains(&crossing) {
+                        queue.push_back(crossing);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn propagate(&self, grid: &mut Grid, mut queue: VecDeque<Line>) -> Result<(), &'static str> {
+        while let Some(line) = queue.pop_front() {
+            self.solve_line(grid, line, &mut queue)?;
+        }
+        Ok(())
+    }
+
+    // Guesses the first unknown cell once line solving stalls, stopping after two solutions
+    fn search(&self, mut grid: Grid, queue: VecDeque<Line>, found: &mut Vec<Grid>) {
+        if found.len() >= 2 || self.propagate(&mut grid, queue).is_err() {
+            return;
+        }
+        let unknown = grid
+            .iter()
+            .enumerate()
+            .find_map(|(row, cells)| Some((row, cells.iter().position(|cell| *cell == b'?')?)));
+        let Some((row, column)) = unknown else {
+            found.push(grid);
+            return;
+        };
+        for guess in [b'#', b'.'] {
+            let mut guessed = grid.clone();
+            guessed[row][column] = guess;
+            self.search(
+                guessed,
+                VecDeque::from([Line::Row(row), Line::Column(column)]),
+                found,
+            );
+        }
+    }
+
+    pub fn solve(&self) -> Solutions {
+        let grid = vec![vec![b'?'; self.columns.len()]; self.rows.len()];
+        let queue = (0..self.rows.len())
+            .map(Line::Row)
+            .chain((0..self.columns.len()).map(Line::Column))
+            .collect();
+        let mut found = Vec::new();
+        self.search(grid, queue, &mut found);
+        let mut found = found.into_iter();
+        match (found.next(), found.next()) {
+            (None, _) => Solutions::Unsolvable,
+            (Some(solution), None) => Solutions::Unique(solution),
+            (Some(first), Some(second)) => Solutions::Multiple(first, second),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn satisfies(nonogram: &Nonogram, grid: &Grid) -> bool {
+        let clue_of = |cells: Vec<u8>| -> Vec<usize> {
+            cells
+                .split(|cell| *cell == b'.')
+                .map(|block| block.len())
+                .filter(|size| *size > 0)
+                .collect()
+        };
+        (0..nonogram.rows.len())
+            .all(|row| clue_of(nonogram.line_cells(grid, Line::Row(row))) == nonogram.rows[row])
+            && (0..nonogram.columns.len()).all(|column| {
+                clue_of(nonogram.line_cells(grid, Line::Column(column))) == nonogram.columns[column]
+            })
+    }
+
+    #[test]
+    fn test_solve() {
+        let heart = Nonogram::parse("rows\n1,1\n5\n5\n3\n1\ncolumns\n2\n4\n4\n4\n2").unwrap();
+        assert_eq!(
+            heart.solve(),
+            Solutions::Unique(
+                [".#.#.", "#####", "#####", ".###.", "..#.."]
+                    .iter()
+                    .map(|row| row.as_bytes().to_vec())
+                    .collect()
+            )
+        );
+
+        // Line solving alone cannot decide between the two diagonals
+        let diagonal = Nonogram::parse("rows\n1\n1\ncolumns\n1\n1").unwrap();
+        match diagonal.solve() {
+            Solutions::Multiple(first, second) => {
+                assert_ne!(first, second);
+                assert!(satisfies(&diagonal, &first) && satisfies(&diagonal, &second));
+            }
+            solutions => panic!("Expected two solutions, got {:?}", solutions),
+        }
+
+        let impossible = Nonogram::parse("rows\n2\n0\ncolumns\n0\n0").unwrap();
+        assert_eq!(impossible.solve(), Solutions::Unsolvable);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Nonogram::parse("rows\n1, 2\n0\n\ncolumns\n3"),
+            Ok(Nonogram {
+                rows: vec![vec![1, 2], vec![]],
+                columns: vec![vec![3]],
+            })
+        );
+        assert_eq!(
+            Nonogram::parse("1\nrows\n1"),
+            Err("Clue outside of a section")
+        );
+        assert_eq!(Nonogram::parse("rows\nx\ncolumns\n1"), Err("Invalid clue"));
+        assert_eq!(Nonogram::parse("rows\n1"), Err("Missing rows or columns"));
+    }
+}
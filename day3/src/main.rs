@@ -1,3 +1,6 @@
+mod symbol_rules;
+
+use crate::symbol_rules::{analyse, SymbolRules};
 use regex::Regex;
 use std::collections::HashSet;
 
@@ -11,10 +14,10 @@ struct Coord {
 }
 
 impl Coord {
-    fn get_neighbours(&self) -> HashSet<Coord> {
+    fn get_neighbours(&self, radius: i32) -> HashSet<Coord> {
         let mut result = HashSet::new();
-        for i in -1..2 {
-            for j in -1..2 {
+        for i in -radius..=radius {
+            for j in -radius..=radius {
                 if i != 0 || j != 0 {
                     result.insert(Coord {
                         x: self.x + i,
@@ -55,84 +58,42 @@ fn extract_engines(input: &str, y: i32) -> Vec<EnginePart> {
     return engine_parts;
 }
 
-fn star_two(input_str: &str) -> u32 {
-    let mut sum = 0;
-    let mut line_num: i32 = 0;
-    let mut gear_coords = HashSet::new();
-    let mut engine_parts: Vec<EnginePart> = Vec::new();
-    for input_line in input_str.lines() {
-        engine_parts.append(&mut extract_engines(input_line, line_num));
-        let mut char_num: i32 = 0;
-        for input_char in input_line.chars() {
-            if input_char == '*' {
-                gear_coords.insert(Coord {
-                    x: char_num,
-                    y: line_num,
-                });
-            }
-            char_num += 1;
-        }
-        line_num += 1;
-    }
-
-    for gear_coord in &gear_coords {
-        let neighbours = gear_coord.get_neighbours();
-        let engine_parts_next_to_gear = engine_parts
-            .iter()
-            .filter(|engine_part| {
-                engine_part
-                    .coords
-                    .iter()
-                    .any(|engine_coord| neighbours.contains(engine_coord))
-            })
-            .cloned()
-            .collect::<Vec<EnginePart>>();
-        if engine_parts_next_to_gear.len() == 2 {
-            sum += engine_parts_next_to_gear[0].value * engine_parts_next_to_gear[1].value;
-        }
-    }
+fn star_two(input_str: &str) -> u64 {
+    analyse(input_str, &SymbolRules::default()).gear_sum()
+}
 
-    return sum;
+fn star_one(input_str: &str) -> u64 {
+    analyse(input_str, &SymbolRules::default()).part_sum()
 }
 
-fn star_one(input_str: &str) -> u32 {
-    let mut sum = 0;
-    let mut line_num: i32 = 0;
-    let mut symbol_coords = HashSet::new();
-    let mut engine_parts: Vec<EnginePart> = Vec::new();
-    for input_line in input_str.lines() {
-        engine_parts.append(&mut extract_engines(input_line, line_num));
-        let mut char_num: i32 = 0;
-        for input_char in input_line.chars() {
-            if !input_char.is_numeric() && input_char != '.' {
-                symbol_coords.insert(Coord {
-                    x: char_num,
-                    y: line_num,
-                });
-            }
-            char_num += 1;
-        }
-        line_num += 1;
-    }
+fn main() {
+    assert_eq!(star_one(EXAMPLE), 4361);
+    assert_eq!(star_two(EXAMPLE), 467835);
 
-    for engine_part in &engine_parts {
-        let mut neighbours = HashSet::new();
-        for coord in &engine_part.coords {
-            neighbours.extend(coord.get_neighbours());
-        }
-        let a: HashSet<Coord> = neighbours.intersection(&symbol_coords).cloned().collect();
-        if a.len() > 0 {
-            sum += engine_part.value;
-        }
+    let sum_rules: SymbolRules = "gears=*:+1:sum".parse().unwrap();
+    let example_report = analyse(EXAMPLE, &sum_rules);
+    assert_eq!(example_report.gear_sum(), 467 + 35 + 617 + 755 + 598);
+    assert_eq!(
+        example_report
+            .unattached_parts
+            .iter()
+            .map(|part| part.value)
+            .collect::<Vec<u32>>(),
+        vec![114, 58]
+    );
+    for symbol_report in &example_report.symbols {
         println!(
-            "for engine {:?} neighbours are {:?}",
-            engine_part, neighbours
+            "{} at ({}, {}): parts {:?}, gear value {:?}",
+            symbol_report.symbol,
+            symbol_report.coord.x,
+            symbol_report.coord.y,
+            symbol_report.parts,
+            symbol_report.gear_value
         );
     }
-    return sum;
-}
+    for (symbol, stats) in &example_report.per_symbol {
+        println!("{}: {:?}", symbol, stats);
+    }
 
-fn main() {
-    println!("Example: {}", star_two(EXAMPLE));
-    println!("Input: {}", star_two(INPUT));
+    println!("Input: {} {}", star_one(INPUT), star_two(INPUT));
 }
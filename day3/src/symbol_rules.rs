@@ -0,0 +1,261 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+
+use crate::{extract_engines, Coord, EnginePart};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolClass {
+    AnyExcept(Vec<char>),
+    OneOf(Vec<char>),
+}
+
+impl SymbolClass {
+    pub fn matches(&self, c: char) -> bool {
+        if c.is_ascii_digit() {
+            return false;
+        }
+        match self {
+            SymbolClass::AnyExcept(blanks) => !blanks.contains(&c),
+            SymbolClass::OneOf(symbols) => symbols.contains(&c),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    fn accepts(&self, number_of_parts: usize) -> bool {
+        match self {
+            Arity::Exactly(n) => number_of_parts == *n,
+            Arity::AtLeast(n) => number_of_parts >= *n,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregation {
+    Product,
+    Sum,
+}
+
+impl Aggregation {
+    fn aggregate(&self, values: &[u32]) -> u64 {
+        match self {
+            Aggregation::Product => values.iter().map(|v| *v as u64).product(),
+            Aggregation::Sum => values.iter().map(|v| *v as u64).sum(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GearRule {
+    pub class: SymbolClass,
+    pub arity: Arity,
+    pub aggregation: Aggregation,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolRules {
+    pub symbols: SymbolClass,
+    pub radius: i32,
+    pub gears: Option<GearRule>,
+}
+
+impl Default for SymbolRules {
+    fn default() -> Self {
+        Self {
+            symbols: SymbolClass::AnyExcept(vec!['.']),
+            radius: 1,
+            gears: Some(GearRule {
+                class: SymbolClass::OneOf(vec!['*']),
+                arity: Arity::Exactly(2),
+                aggregation: Aggregation::Product,
+            }),
+        }
+    }
+}
+
+fn parse_class(value: &str) -> SymbolClass {
+    match value.strip_prefix('!') {
+        Some(blanks) => SymbolClass::AnyExcept(blanks.chars().collect()),
+        None => SymbolClass::OneOf(value.chars().collect()),
+    }
+}
+
+fn parse_gear_rule(value: &str) -> Result<GearRule, &'static str> {
+    let split_value = value.split(':').collect::<Vec<&str>>();
+    if split_value.len() != 3 {
+        return Err("Gear rule must look like <symbols>:<arity>:<aggregation>");
+    }
+    let arity = match split_value[1].strip_prefix('+') {
+        Some(n) => Arity::AtLeast(n.parse().map_err(|_| "Invalid gear arity")?),
+        None => Arity::Exactly(split_value[1].parse().map_err(|_| "Invalid gear arity")?),
+    };
+    let aggregation = match split_value[2] {
+        "product" => Aggregation::Product,
+        "sum" => Aggregation::Sum,
+        _ => return Err("Unknown gear aggregation"),
+    };
+    Ok(GearRule {
+        class: parse_class(split_value[0]),
+        arity,
+        aggregation,
+    })
+}
+
+// Rules can be given as whitespace separated key=value pairs, e.g.
+// "symbols=!. radius=1 gears=*:2:product" (the default rules) or
+// "symbols=#$ gears=#:+3:sum". A leading '!' on a symbol class means "anything but".
+impl FromStr for SymbolRules {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = SymbolRules::default();
+        for setting in s.split_whitespace() {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or("Settings must be key=value")?;
+            match key {
+                "symbols" => rules.symbols = parse_class(value),
+                "radius" => rules.radius = value.parse().map_err(|_| "Invalid radius")?,
+                "gears" if value == "none" => rules.gears = None,
+                "gears" => rules.gears = Some(parse_gear_rule(value)?),
+                _ => return Err("Unknown setting"),
+            }
+        }
+        Ok(rules)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SymbolReport {
+    pub symbol: char,
+    pub coord: Coord,
+    pub parts: Vec<u32>,
+    pub gear_value: Option<u64>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SymbolStats {
+    pub occurrences: usize,
+    pub attached_parts: usize,
+    pub part_value_sum: u64,
+    pub gears: usize,
+    pub gear_value_sum: u64,
+}
+
+#[derive(Debug)]
+pub struct SchematicReport {
+    pub symbols: Vec<SymbolReport>,
+    pub per_symbol: BTreeMap<char, SymbolStats>,
+    pub attached_parts: Vec<EnginePart>,
+    pub unattached_parts: Vec<EnginePart>,
+}
+
+impl SchematicReport {
+    pub fn part_sum(&self) -> u64 {
+        self.attached_parts
+            .iter()
+            .map(|part| part.value as u64)
+            .sum()
+    }
+
+    pub fn gear_sum(&self) -> u64 {
+        self.symbols
+            .iter()
+            .filter_map(|symbol| symbol.gear_value)
+            .sum()
+    }
+}
+
+pub fn analyse(input_str: &str, rules: &SymbolRules) -> SchematicReport {
+    let mut engine_parts: Vec<EnginePart> = Vec::new();
+    let mut symbols: Vec<(char, Coord)> = Vec::new();
+    for (line_num, input_line) in input_str.lines().enumerate() {
+        engine_parts.append(&mut extract_engines(input_line, line_num as i32));
+        for (char_num, input_char) in input_line.chars().enumerate() {
+            let is_gear = rules
+                .gears
+                .as_ref()
+                .is_some_and(|gear_rule| gear_rule.class.matches(input_char));
+            if is_gear || rules.symbols.matches(input_char) {
+                symbols.push((
+                    input_char,
+                    Coord {
+                        x: char_num as i32,
+                        y: line_num as i32,
+                    },
+                ));
+            }
+        }
+    }
+
+    let mut part_index_by_coord: HashMap<&Coord, usize> = HashMap::new();
+    for (part_index, engine_part) in engine_parts.iter().enumerate() {
+        for coord in &engine_part.coords {
+            part_index_by_coord.insert(coord, part_index);
+        }
+    }
+
+    let mut attached: BTreeSet<usize> = BTreeSet::new();
+    let mut per_symbol: BTreeMap<char, SymbolStats> = BTreeMap::new();
+    let mut symbol_reports = Vec::new();
+    for (symbol, coord) in symbols {
+        let part_indices: BTreeSet<usize> = coord
+            .get_neighbours(rules.radius)
+            .iter()
+            .filter_map(|neighbour| part_index_by_coord.get(neighbour).copied())
+            .collect();
+        let parts: Vec<u32> = part_indices
+            .iter()
+            .map(|part_index| engine_parts[*part_index].value)
+            .collect();
+        let gear_value = rules.gears.as_ref().and_then(|gear_rule| {
+            if gear_rule.class.matches(symbol) && gear_rule.arity.accepts(parts.len()) {
+                Some(gear_rule.aggregation.aggregate(&parts))
+            } else {
+                None
+            }
+        });
+
+        let stats = per_symbol.entry(symbol).or_default();
+        stats.occurrences += 1;
+        stats.attached_parts += parts.len();
+        stats.part_value_sum += parts.iter().map(|value| *value as u64).sum::<u64>();
+        if let Some(value) = gear_value {
+            stats.gears += 1;
+            stats.gear_value_sum += value;
+        }
+        if rules.symbols.matches(symbol) {
+            attached.extend(part_indices);
+        }
+
+        symbol_reports.push(SymbolReport {
+            symbol,
+            coord,
+            parts,
+            gear_value,
+        });
+    }
+
+    let mut attached_parts = Vec::new();
+    let mut unattached_parts = Vec::new();
+    for (part_index, engine_part) in engine_parts.into_iter().enumerate() {
+        if attached.contains(&part_index) {
+            attached_parts.push(engine_part);
+        } else {
+            unattached_parts.push(engine_part);
+        }
+    }
+
+    SchematicReport {
+        symbols: symbol_reports,
+        per_symbol,
+        attached_parts,
+        unattached_parts,
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+use crate::{parse_game_data, GameData};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardCopies {
+    pub card_id: u32,
+    pub wins: usize,
+    pub copies: u64,
+    pub copies_granted: u64,
+}
+
+#[derive(Debug)]
+pub struct Cascade {
+    pub cards: Vec<CardCopies>,
+}
+
+impl GameData {
+    pub fn number_of_wins(&self) -> usize {
+        self.my_tickets.intersection(&self.winning_tickets).count()
+    }
+}
+
+impl Cascade {
+    pub fn simulate(mut deck: Vec<GameData>) -> Result<Self, &'static str> {
+        deck.sort_by_key(|game_data| game_data.card_id);
+        if deck
+            .windows(2)
+            .any(|pair| pair[1].card_id != pair[0].card_id + 1)
+        {
+            return Err("Card ids are not contiguous");
+        }
+
+        let mut copies: Vec<u64> = vec![1; deck.len()];
+        let mut cards = Vec::with_capacity(deck.len());
+        for (index, game_data) in deck.iter().enumerate() {
+            let wins = game_data.number_of_wins();
+            let won_cards = (index + 1)..usize::min(index + 1 + wins, deck.len());
+            for won_card in won_cards.clone() {
+                copies[won_card] = copies[won_card]
+                    .checked_add(copies[index])
+                    .ok_or("Copy count overflowed u64")?;
+            }
+            let copies_granted = copies[index]
+                .checked_mul(won_cards.len() as u64)
+                .ok_or("Copy count overflowed u64")?;
+            cards.push(CardCopies {
+                card_id: game_data.card_id,
+                wins,
+                copies: copies[index],
+                copies_granted,
+            });
+        }
+        Ok(Cascade { cards })
+    }
+
+    pub fn total_copies(&self) -> Result<u64, &'static str> {
+        self.cards.iter().try_fold(0u64, |sum, card| {
+            sum.checked_add(card.copies)
+                .ok_or("Copy count overflowed u64")
+        })
+    }
+
+    pub fn top_contributor(&self) -> Option<&CardCopies> {
+        self.cards
+            .iter()
+            .filter(|card| card.copies_granted > 0)
+            .max_by_key(|card| (card.copies_granted, std::cmp::Reverse(card.card_id)))
+    }
+}
+
+// Only the copies won for the next few cards are kept around, so the deck never has to be in memory.
+#[derive(Debug, Default)]
+pub struct StreamingCascade {
+    pending: VecDeque<u64>,
+    // Cards still granting copies to the cards that follow, credited as those cards arrive
+    granting: Vec<CardCopies>,
+    last_card_id: Option<u32>,
+    pub cards_processed: usize,
+    pub total_copies: u64,
+    pub top_contributor: Option<CardCopies>,
+}
+
+impl StreamingCascade {
+    pub fn push(&mut self, game_data: &GameData) -> Result<u64, &'static str> {
+        if let Some(last_card_id) = self.last_card_id {
+            if game_data.card_id != last_card_id + 1 {
+                return Err("Card ids are not contiguous");
+            }
+        }
+        self.last_card_id = Some(game_data.card_id);
+
+        let copies = self
+            .pending
+            .pop_front()
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or("Copy count overflowed u64")?;
+        self.credit_granting(game_data.card_id)?;
+        let wins = game_data.number_of_wins();
+        if wins > 0 {
+            self.granting.push(CardCopies {
+                card_id: game_data.card_id,
+                wins,
+                copies,
+                copies_granted: 0,
+            });
+        }
+        if self.pending.len() < wins {
+            self.pending.resize(wins, 0);
+        }
+        for pending_copies in self.pending.iter_mut().take(wins) {
+            *pending_copies = pending_copies
+                .checked_add(copies)
+                .ok_or("Copy count overflowed u64")?;
+        }
+
+        self.cards_processed += 1;
+        self.total_copies = self
+            .total_copies
+            .checked_add(copies)
+            .ok_or("Copy count overflowed u64")?;
+        Ok(copies)
+    }
+
+    // Copies are only granted to cards that exist, the same as Cascade::top_contributor
+    fn credit_granting(&mut self, card_id: u32) -> Result<(), &'static str> {
+        for card in self.granting.iter_mut() {
+            card.copies_granted = card
+                .copies_granted
+                .checked_add(card.copies)
+                .ok_or("Copy count overflowed u64")?;
+            let key = |card: &CardCopies| (card.copies_granted, std::cmp::Reverse(card.card_id));
+            if self
+                .top_contributor
+                .as_ref()
+                .is_none_or(|top| top.card_id == card.card_id || key(card) > key(top))
+            {
+                self.top_contributor = Some(card.clone());
+            }
+        }
+        self.granting
+            .retain(|card| card.card_id + card.wins as u32 > card_id);
+        Ok(())
+    }
+
+    pub fn run(reader: impl BufRead) -> Result<Self, &'static str> {
+        let mut cascade = StreamingCascade::default();
+        for line in reader.lines() {
+            let line = line.map_err(|_| "Failed to read card")?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let game_data = parse_game_data(&line).ok_or("Invalid card")?;
+            cascade.push(&game_data)?;
+        }
+        Ok(cascade)
+    }
+}
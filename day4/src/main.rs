@@ -1,5 +1,8 @@
+mod cascade;
+
+use crate::cascade::{Cascade, StreamingCascade};
 use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 const EXAMPLE: &str = include_str!("example.txt");
 const INPUT: &str = include_str!("input.txt");
@@ -52,42 +55,42 @@ fn star_one(input_str: &str) -> u32 {
     return sum as u32;
 }
 
-fn star_two(input_str: &str) -> u32 {
-    let mut sum = 0;
-    let mut number_of_wins_per_id = HashMap::new();
-    let mut copies: HashMap<u32, u32> = HashMap::new();
-    for input_line in input_str.lines() {
-        let game_data = parse_game_data(input_line).unwrap();
-        let num_of_matching_tickets = game_data
-            .my_tickets
-            .intersection(&game_data.winning_tickets)
-            .cloned()
-            .collect::<Vec<u32>>()
-            .len();
-        if num_of_matching_tickets > 0 {
-            number_of_wins_per_id.insert(game_data.card_id, num_of_matching_tickets);
-        }
-        copies.insert(game_data.card_id, 1);
-    }
-    let mut num_wins_per_id_vec = number_of_wins_per_id.iter().collect::<Vec<_>>();
-    num_wins_per_id_vec.sort();
+fn parse_deck(input_str: &str) -> Vec<GameData> {
+    input_str
+        .lines()
+        .map(|input_line| parse_game_data(input_line).unwrap())
+        .collect()
+}
 
-    for (id, num_wins) in num_wins_per_id_vec {
-        let number_of_copies_for_this_id = *copies.get(id).unwrap();
-        for i in 1..num_wins + 1 {
-            *copies
-                .entry(id + i as u32)
-                .and_modify(|v| *v += 1 * number_of_copies_for_this_id)
-                .or_insert(1);
-        }
-    }
-    for (_, num) in copies {
-        sum += num;
-    }
-    return sum as u32;
+fn star_two(input_str: &str) -> u64 {
+    let cascade = Cascade::simulate(parse_deck(input_str)).unwrap();
+    cascade.total_copies().unwrap()
 }
 
 fn main() {
-    println!("Example: {}", star_two(EXAMPLE));
+    assert_eq!(star_one(EXAMPLE), 13);
+    assert_eq!(star_two(EXAMPLE), 30);
+
+    let example_cascade = Cascade::simulate(parse_deck(EXAMPLE)).unwrap();
+    for card in &example_cascade.cards {
+        println!(
+            "Card {}: {} wins, {} copies, granted {} copies",
+            card.card_id, card.wins, card.copies, card.copies_granted
+        );
+    }
+    assert_eq!(example_cascade.top_contributor().unwrap().card_id, 3);
+
+    let streamed = StreamingCascade::run(EXAMPLE.as_bytes()).unwrap();
+    assert_eq!(streamed.total_copies, 30);
+    assert_eq!(
+        streamed.top_contributor.as_ref(),
+        example_cascade.top_contributor()
+    );
+    // Card 1 wins two copies but only one card follows it
+    let short_deck = "Card 1: 1 2 | 1 2\nCard 2: 1 | 1";
+    let streamed_short = StreamingCascade::run(short_deck.as_bytes()).unwrap();
+    let top = streamed_short.top_contributor.unwrap();
+    assert_eq!((top.card_id, top.copies_granted), (1, 1));
+
     println!("Input: {}", star_two(INPUT));
 }
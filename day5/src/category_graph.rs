@@ -0,0 +1,100 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::Map;
+
+#[derive(Debug)]
+pub struct TransformStep {
+    pub source: String,
+    pub destination: String,
+    pub maps: Vec<Map>,
+}
+
+#[derive(Debug)]
+pub struct CategoryGraph {
+    steps: Vec<TransformStep>,
+    steps_by_source: HashMap<String, Vec<usize>>,
+}
+
+impl CategoryGraph {
+    pub fn new(steps: Vec<TransformStep>) -> Result<Self, &'static str> {
+        let mut steps_by_source: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut seen_edges: HashSet<(&str, &str)> = HashSet::new();
+        for (step_index, step) in steps.iter().enumerate() {
+            if step.source == step.destination {
+                return Err("A map converts a category to itself");
+            }
+            if !seen_edges.insert((&step.source, &step.destination)) {
+                return Err("The same conversion is defined twice");
+            }
+            steps_by_source
+                .entry(step.source.clone())
+                .or_default()
+                .push(step_index);
+        }
+        Ok(Self {
+            steps,
+            steps_by_source,
+        })
+    }
+
+    pub fn steps(&self) -> &[TransformStep] {
+        &self.steps
+    }
+
+    pub fn has_category(&self, category: &str) -> bool {
+        self.steps
+            .iter()
+            .any(|step| step.source == category || step.destination == category)
+    }
+
+    // Shortest chain of steps converting `from` into `to`
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<&TransformStep>, &'static str> {
+        if !self.has_category(from) || !self.has_category(to) {
+            return Err("Unknown category");
+        }
+        let mut reached_by: HashMap<&str, Option<usize>> = HashMap::from([(from, None)]);
+        let mut queue: VecDeque<&str> = VecDeque::from([from]);
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                break;
+            }
+            for step_index in self.steps_by_source.get(category).into_iter().flatten() {
+                let destination = self.steps[*step_index].destination.as_str();
+                if !reached_by.contains_key(destination) {
+                    reached_by.insert(destination, Some(*step_index));
+                    queue.push_back(destination);
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut category = to;
+        while category != from {
+            let step_index = reached_by
+                .get(category)
+                .ok_or("No conversion chain between the categories")?
+                .unwrap();
+            path.push(&self.steps[step_index]);
+            category = &self.steps[step_index].source;
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    pub fn conversion(&self, from: &str, to: &str) -> Result<Vec<&[Map]>, &'static str> {
+        Ok(self
+            .path(from, to)?
+            .into_iter()
+            .map(|step| step.maps.as_slice())
+            .collect())
+    }
+
+    // A well formed almanac is a single chain that uses every step exactly once
+    pub fn validate_chain(&self, from: &str, to: &str) -> Result<(), &'static str> {
+        let path = self.path(from, to)?;
+        if path.len() != self.steps.len() {
+            return Err("Some maps are not part of the conversion chain");
+        }
+        Ok(())
+    }
+}
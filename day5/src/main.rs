@@ -1,6 +1,12 @@
+mod category_graph;
+mod piecewise_map;
 mod seed_range;
+mod validation;
 
-use crate::seed_range::SeedRange;
+use crate::category_graph::{CategoryGraph, TransformStep};
+use crate::piecewise_map::PiecewiseMap;
+use crate::seed_range::{merge_ranges, SeedRange};
+use crate::validation::validate;
 
 const EXAMPLE: &str = include_str!("example.txt");
 const INPUT: &str = include_str!("input.txt");
@@ -18,15 +24,19 @@ impl Map {
     }
 
     fn overlaps_with(&self, seed: &SeedRange) -> bool {
-        if seed.end < self.source {
+        if seed.end <= self.source {
             return false;
         }
-        if seed.start > self.source + self.length {
+        if seed.start >= self.source + self.length {
             return false;
         }
         return true;
     }
 
+    fn source_range(&self) -> SeedRange {
+        SeedRange::new(self.source, self.length)
+    }
+
     fn transform_seed_range(&self, seed: &SeedRange) -> Option<SeedRange> {
         let diff = seed.start - self.source;
         if self.fully_contained(seed) {
@@ -36,31 +46,48 @@ impl Map {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SeedInterpretation {
+    Discrete,
+    Ranges,
+}
+
 #[derive(Debug)]
 struct GameData {
     seeds: Vec<SeedRange>,
-    transform_steps: Vec<Vec<Map>>,
+    categories: CategoryGraph,
 }
 
-fn parse_seeds(line: &str) -> Vec<SeedRange> {
+fn parse_seeds(
+    line: &str,
+    interpretation: SeedInterpretation,
+) -> Result<Vec<SeedRange>, &'static str> {
     let values: Vec<i64> = line
+        .strip_prefix("seeds:")
+        .ok_or("No seeds")?
         .split_whitespace()
-        .skip(1)
-        .filter_map(|s| s.parse().ok())
-        .collect();
-
-    // Create pairs of adjacent elements
-    let pairs: Vec<SeedRange> = values
-        .chunks(2)
-        .filter_map(|chunk| {
-            if chunk.len() == 2 {
-                Some(SeedRange::new(chunk[0], chunk[1]))
-            } else {
-                None
+        .map(|s| s.parse().map_err(|_| "Invalid seed"))
+        .collect::<Result<Vec<i64>, &'static str>>()?;
+
+    match interpretation {
+        SeedInterpretation::Discrete => {
+            Ok(values.iter().map(|seed| SeedRange::new(*seed, 1)).collect())
+        }
+        SeedInterpretation::Ranges => {
+            if !values.len().is_multiple_of(2) {
+                return Err("Seed ranges must come in pairs");
             }
-        })
-        .collect();
-    return pairs;
+            Ok(values
+                .chunks(2)
+                .map(|chunk| SeedRange::new(chunk[0], chunk[1]))
+                .collect())
+        }
+    }
+}
+
+fn parse_header(line: &str) -> Option<(String, String)> {
+    let (source, destination) = line.strip_suffix(" map:")?.split_once("-to-")?;
+    Some((source.to_string(), destination.to_string()))
 }
 
 fn parse_map(line: &str) -> Result<Map, &'static str> {
@@ -81,32 +108,37 @@ fn parse_map(line: &str) -> Result<Map, &'static str> {
     });
 }
 
-fn parse_game_data(input_str: &str) -> Result<GameData, &'static str> {
+fn parse_game_data(
+    input_str: &str,
+    interpretation: SeedInterpretation,
+) -> Result<GameData, &'static str> {
     let mut lines = input_str.lines();
 
-    // Parse seeds
-    let seeds: Vec<SeedRange> = parse_seeds(lines.next().unwrap());
-    let mut maps: Vec<Vec<Map>> = Vec::new();
-    maps.push(Vec::new());
-    let mut map_to_collect_to: &mut Vec<Map> = maps.last_mut().unwrap();
+    let seeds: Vec<SeedRange> = parse_seeds(lines.next().ok_or("Empty almanac")?, interpretation)?;
+    let mut steps: Vec<TransformStep> = Vec::new();
 
     for line in lines {
         if line.is_empty() {
             continue;
         }
-        if line.contains("map") {
-            if !map_to_collect_to.is_empty() {
-                maps.push(Vec::new());
-                map_to_collect_to = maps.last_mut().unwrap();
-            }
+        if let Some((source, destination)) = parse_header(line) {
+            steps.push(TransformStep {
+                source,
+                destination,
+                maps: Vec::new(),
+            });
         } else {
-            map_to_collect_to.push(parse_map(line).unwrap());
+            steps
+                .last_mut()
+                .ok_or("Map values before the first map header")?
+                .maps
+                .push(parse_map(line)?);
         }
     }
 
     Ok(GameData {
         seeds,
-        transform_steps: maps,
+        categories: CategoryGraph::new(steps)?,
     })
 }
 
@@ -119,7 +151,7 @@ fn split_at_map(seed_range: &SeedRange, map: &Map) -> Vec<SeedRange> {
     return result;
 }
 
-fn split_at_maps(seed_range: &SeedRange, maps: &Vec<Map>) -> Vec<SeedRange> {
+fn split_at_maps(seed_range: &SeedRange, maps: &[Map]) -> Vec<SeedRange> {
     let mut result = vec![seed_range.clone()];
     for map in maps {
         let mut split_for_this_map: Vec<SeedRange> = Vec::new();
@@ -131,7 +163,7 @@ fn split_at_maps(seed_range: &SeedRange, maps: &Vec<Map>) -> Vec<SeedRange> {
     return result;
 }
 
-fn split_vec_at_maps(seed_ranges: &Vec<SeedRange>, maps: &Vec<Map>) -> Vec<SeedRange> {
+fn split_vec_at_maps(seed_ranges: &Vec<SeedRange>, maps: &[Map]) -> Vec<SeedRange> {
     let mut result = Vec::new();
     for seed_range in seed_ranges {
         result.append(&mut split_at_maps(seed_range, maps));
@@ -139,13 +171,14 @@ fn split_vec_at_maps(seed_ranges: &Vec<SeedRange>, maps: &Vec<Map>) -> Vec<SeedR
     return result;
 }
 
-fn star_two(input_str: &str) -> i64 {
-    let game_data = parse_game_data(input_str).unwrap();
+fn star_two_layered(input_str: &str) -> i64 {
+    let game_data = parse_game_data(input_str, SeedInterpretation::Ranges).unwrap();
+    let transform_steps = game_data.categories.conversion("seed", "location").unwrap();
     let mut overall_min = i64::MAX;
     for seed in game_data.seeds {
         let mut seeds = vec![seed];
         let mut next_phase_seeds: Vec<SeedRange> = Vec::new();
-        for transform_step in &game_data.transform_steps {
+        for &transform_step in &transform_steps {
             let split_seeds = split_vec_at_maps(&seeds, transform_step);
             for split_seed in &split_seeds {
                 let mut transformed = false;
@@ -174,6 +207,177 @@ fn star_two(input_str: &str) -> i64 {
     return overall_min;
 }
 
+fn fold_transform_steps(transform_steps: &[&[Map]]) -> PiecewiseMap {
+    transform_steps
+        .iter()
+        .fold(PiecewiseMap::identity(), |folded, transform_step| {
+            folded.compose(&PiecewiseMap::from_maps(transform_step))
+        })
+}
+
+fn invert_transform_step(transform_step: &[Map], targets: &[SeedRange]) -> Vec<SeedRange> {
+    let step_map = PiecewiseMap::from_maps(transform_step);
+    merge_ranges(
+        targets
+            .iter()
+            .flat_map(|target| step_map.preimage(target))
+            .collect(),
+    )
+}
+
+// Every seed range that ends up in one of the locations, checked against the folded map in both directions
+fn run_backwards(
+    transform_steps: &[&[Map]],
+    locations: &[SeedRange],
+) -> Result<Vec<SeedRange>, &'static str> {
+    // Touching windows are merged so an image piece may span several of them
+    let locations = merge_ranges(locations.to_vec());
+    let seeds = transform_steps
+        .iter()
+        .rev()
+        .fold(locations.clone(), |targets, transform_step| {
+            invert_transform_step(transform_step, &targets)
+        });
+
+    let seed_to_location = fold_transform_steps(transform_steps);
+    let folded_seeds = merge_ranges(
+        locations
+            .iter()
+            .flat_map(|location| seed_to_location.preimage(location))
+            .collect(),
+    );
+    if seeds != folded_seeds {
+        return Err("Layered and folded inverse disagree");
+    }
+    let lands_in_locations = seeds
+        .iter()
+        .flat_map(|seed_range| seed_to_location.apply_range(seed_range))
+        .all(|image| {
+            locations
+                .iter()
+                .any(|location| location.contains_range(&image))
+        });
+    if !lands_in_locations {
+        return Err("Inverse seeds map outside of the locations");
+    }
+    Ok(seeds)
+}
+
+fn seeds_for_location(
+    transform_steps: &[&[Map]],
+    location: i64,
+) -> Result<Vec<SeedRange>, &'static str> {
+    run_backwards(transform_steps, &[SeedRange::new(location, 1)])
+}
+
+fn lowest_location(input_str: &str, interpretation: SeedInterpretation) -> i64 {
+    let game_data = parse_game_data(input_str, interpretation).unwrap();
+    game_data
+        .categories
+        .validate_chain("seed", "location")
+        .unwrap();
+    let transform_steps = game_data.categories.conversion("seed", "location").unwrap();
+    let seed_to_location = fold_transform_steps(&transform_steps);
+    game_data
+        .seeds
+        .iter()
+        .filter_map(|seed_range| seed_to_location.minimum(seed_range))
+        .map(|(_, location)| location)
+        .min()
+        .unwrap()
+}
+
+fn check(input_str: &str) {
+    let game_data = parse_game_data(input_str, SeedInterpretation::Ranges).unwrap();
+    if let Err(error) = game_data.categories.validate_chain("seed", "location") {
+        println!("Category chain: {}", error);
+    }
+    let report = validate(&game_data.categories);
+    for diagnostic in &report.diagnostics {
+        println!("{}", diagnostic);
+    }
+    for step_coverage in &report.coverage {
+        println!(
+            "{}: domain {:?}, {} mapped, {} identity passthrough ({:.1}%)",
+            step_coverage.step,
+            step_coverage.domain,
+            step_coverage.mapped,
+            step_coverage.passthrough,
+            step_coverage.passthrough_ratio() * 100.0
+        );
+    }
+}
+
+fn star_one(input_str: &str) -> i64 {
+    lowest_location(input_str, SeedInterpretation::Discrete)
+}
+
+fn star_two(input_str: &str) -> i64 {
+    lowest_location(input_str, SeedInterpretation::Ranges)
+}
+
 fn main() {
-    println!("Example: {}", star_two(INPUT));
+    if std::env::args().any(|arg| arg == "--check") {
+        check(INPUT);
+    }
+
+    assert_eq!(star_one(EXAMPLE), 35);
+    assert_eq!(star_two(EXAMPLE), 46);
+    assert_eq!(star_two_layered(EXAMPLE), 46);
+
+    let example_data = parse_game_data(EXAMPLE, SeedInterpretation::Ranges).unwrap();
+    let transform_steps = example_data
+        .categories
+        .conversion("seed", "location")
+        .unwrap();
+    let seed_to_location = fold_transform_steps(&transform_steps);
+    println!("Breakpoints: {:?}", seed_to_location.breakpoints());
+    for seed_range in &example_data.seeds {
+        println!(
+            "{:?} -> {:?}, minimum can occur at {:?}",
+            seed_range,
+            seed_to_location.apply_range(seed_range),
+            seed_to_location.minimum_candidates(seed_range)
+        );
+    }
+
+    let seeds_for_lowest = seeds_for_location(&transform_steps, 46).unwrap();
+    assert!(seeds_for_lowest.contains(&SeedRange::new(82, 1)));
+    let seeds_for_window = run_backwards(&transform_steps, &[SeedRange::new(40, 20)]).unwrap();
+    println!("Seeds ending up in 40..60: {:?}", seeds_for_window);
+    assert_eq!(
+        run_backwards(
+            &transform_steps,
+            &[SeedRange::new(40, 5), SeedRange::new(45, 5)]
+        ),
+        run_backwards(&transform_steps, &[SeedRange::new(40, 10)])
+    );
+    assert!(run_backwards(&transform_steps, &[SeedRange::new(40, 10)]).is_ok());
+
+    for step in example_data.categories.path("seed", "location").unwrap() {
+        println!(
+            "{} -> {}: {} maps",
+            step.source,
+            step.destination,
+            step.maps.len()
+        );
+    }
+    let soil_to_humidity = fold_transform_steps(
+        &example_data
+            .categories
+            .conversion("soil", "humidity")
+            .unwrap(),
+    );
+    assert_eq!(soil_to_humidity.apply(81), 78);
+    assert!(example_data
+        .categories
+        .conversion("location", "seed")
+        .is_err());
+
+    let example_report = validate(&example_data.categories);
+    assert!(example_report.diagnostics.is_empty());
+    assert_eq!(example_report.coverage[0].passthrough, 0);
+    assert_eq!(example_report.coverage[3].passthrough, 0);
+
+    println!("Input: {} {}", star_one(INPUT), star_two(INPUT));
 }
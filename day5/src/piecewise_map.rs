@@ -0,0 +1,290 @@
+use crate::seed_range::{merge_ranges, SeedRange};
+use crate::Map;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Segment {
+    pub start: i64,
+    pub end: i64,
+    pub offset: i64,
+}
+
+impl Segment {
+    fn lowest_value(&self) -> i64 {
+        self.start + self.offset
+    }
+}
+
+// The segments cover the whole i64 domain without gaps, values outside of every map are
+// stored as explicit identity (offset 0) segments. Segment ends are exclusive, so i64::MAX
+// itself lies past the last segment and is left unchanged.
+#[derive(Debug, Clone)]
+pub struct PiecewiseMap {
+    segments: Vec<Segment>,
+    // sparse_min[k][i] is the index of the segment with the lowest value among segments i..i + 2^k
+    sparse_min: Vec<Vec<usize>>,
+}
+
+fn paint(segments: &[Segment], start: i64, end: i64, offset: i64) -> Vec<Segment> {
+    let mut result: Vec<Segment> = Vec::new();
+    for segment in segments {
+        if segment.start < start {
+            result.push(Segment {
+                start: segment.start,
+                end: i64::min(segment.end, start),
+                offset: segment.offset,
+            });
+        }
+        if segment.end > end {
+            result.push(Segment {
+                start: i64::max(segment.start, end),
+                end: segment.end,
+                offset: segment.offset,
+            });
+        }
+    }
+    result.push(Segment { start, end, offset });
+    result.sort_by_key(|segment| segment.start);
+    result
+}
+
+impl PiecewiseMap {
+    pub fn identity() -> Self {
+        Self::from_segments(vec![Segment {
+            start: i64::MIN,
+            end: i64::MAX,
+            offset: 0,
+        }])
+    }
+
+    // When maps overlap the first one wins, the same way the layered transform picks the first matching map.
+    pub fn from_maps(maps: &[Map]) -> Self {
+        let mut segments = Self::identity().segments;
+        for map in maps.iter().rev().filter(|map| map.length > 0) {
+            segments = paint(
+                &segments,
+                map.source,
+                map.source + map.length,
+                map.destination - map.source,
+            );
+        }
+        Self::from_segments(segments)
+    }
+
+    fn from_segments(segments: Vec<Segment>) -> Self {
+        let mut merged: Vec<Segment> = Vec::new();
+        for segment in segments
+            .into_iter()
+            .filter(|segment| segment.start < segment.end)
+        {
+            match merged.last_mut() {
+                Some(last) if last.offset == segment.offset && last.end == segment.start => {
+                    last.end = segment.end;
+                }
+                _ => merged.push(segment),
+            }
+        }
+
+        let mut sparse_min: Vec<Vec<usize>> = vec![(0..merged.len()).collect()];
+        let mut width = 1;
+        while width * 2 <= merged.len() {
+            let previous = sparse_min.last().unwrap();
+            let level = (0..=merged.len() - width * 2)
+                .map(|i| {
+                    let (a, b) = (previous[i], previous[i + width]);
+                    if merged[b].lowest_value() < merged[a].lowest_value() {
+                        b
+                    } else {
+                        a
+                    }
+                })
+                .collect();
+            sparse_min.push(level);
+            width *= 2;
+        }
+
+        Self {
+            segments: merged,
+            sparse_min,
+        }
+    }
+
+    fn segment_index(&self, value: i64) -> usize {
+        self.segments
+            .partition_point(|segment| segment.end <= value)
+    }
+
+    // Index of the segment with the lowest value among segments first..=last
+    fn lowest_segment(&self, first: usize, last: usize) -> usize {
+        let level = (usize::BITS - (last - first + 1).leading_zeros() - 1) as usize;
+        let a = self.sparse_min[level][first];
+        let b = self.sparse_min[level][last + 1 - (1 << level)];
+        if self.segments[b].lowest_value() < self.segments[a].lowest_value() {
+            b
+        } else {
+            a
+        }
+    }
+
+    pub fn apply(&self, value: i64) -> i64 {
+        match self.segments.get(self.segment_index(value)) {
+            Some(segment) => value + segment.offset,
+            None => value,
+        }
+    }
+
+    pub fn apply_range(&self, seed_range: &SeedRange) -> Vec<SeedRange> {
+        let mut result = Vec::new();
+        let mut index = self.segment_index(seed_range.start);
+        while index < self.segments.len() && self.segments[index].start < seed_range.end {
+            let segment = &self.segments[index];
+            let start = i64::max(segment.start, seed_range.start);
+            let end = i64::min(segment.end, seed_range.end);
+            result.push(SeedRange {
+                start: start + segment.offset,
+                end: end + segment.offset,
+            });
+            index += 1;
+        }
+        result
+    }
+
+    // Every value that is mapped into the target, identity passthrough included
+    pub fn preimage(&self, target: &SeedRange) -> Vec<SeedRange> {
+        let preimage = self
+            .segments
+            .iter()
+            .filter_map(|segment| {
+                let image = SeedRange {
+                    start: segment.start + segment.offset,
+                    end: segment.end + segment.offset,
+                };
+                image.intersect(target).map(|hit| SeedRange {
+                    start: hit.start - segment.offset,
+                    end: hit.end - segment.offset,
+                })
+            })
+            .collect();
+        merge_ranges(preimage)
+    }
+
+    // The result maps every value v to next.apply(self.apply(v))
+    pub fn compose(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        let mut segments = Vec::new();
+        for segment in &self.segments {
+            let image = SeedRange {
+                start: segment.start + segment.offset,
+                end: segment.end + segment.offset,
+            };
+            let mut index = next.segment_index(image.start);
+            while index < next.segments.len() && next.segments[index].start < image.end {
+                let next_segment = &next.segments[index];
+                segments.push(Segment {
+                    start: i64::max(next_segment.start, image.start) - segment.offset,
+                    end: i64::min(next_segment.end, image.end) - segment.offset,
+                    offset: segment.offset + next_segment.offset,
+                });
+                index += 1;
+            }
+        }
+        Self::from_segments(segments)
+    }
+
+    // Every segment is increasing, so the minimum of a range is at its start or at a segment start inside it.
+    pub fn breakpoints(&self) -> Vec<i64> {
+        self.segments
+            .iter()
+            .skip(1)
+            .map(|segment| segment.start)
+            .collect()
+    }
+
+    pub fn minimum_candidates(&self, seed_range: &SeedRange) -> Vec<i64> {
+        let mut candidates = vec![seed_range.start];
+        candidates.extend(
+            self.breakpoints().into_iter().filter(|breakpoint| {
+                *breakpoint > seed_range.start && *breakpoint < seed_range.end
+            }),
+        );
+        candidates
+    }
+
+    // Returns the (seed, location) pair with the lowest location inside the range in O(log n)
+    pub fn minimum(&self, seed_range: &SeedRange) -> Option<(i64, i64)> {
+        if seed_range.start >= seed_range.end {
+            return None;
+        }
+        let first = self.segment_index(seed_range.start);
+        let last = self.segment_index(seed_range.end - 1);
+        let mut best = (seed_range.start, self.apply(seed_range.start));
+        if first < last {
+            let lowest = &self.segments[self.lowest_segment(first + 1, last)];
+            if lowest.lowest_value() < best.1 {
+                best = (lowest.start, lowest.lowest_value());
+            }
+        }
+        Some(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compose() {
+        let first = PiecewiseMap::from_maps(&[Map {
+            source: 98,
+            destination: 50,
+            length: 2,
+        }]);
+        let second = PiecewiseMap::from_maps(&[Map {
+            source: 40,
+            destination: 0,
+            length: 20,
+        }]);
+        let composed = first.compose(&second);
+        for value in 0..120 {
+            assert_eq!(composed.apply(value), second.apply(first.apply(value)));
+        }
+        assert_eq!(composed.breakpoints(), vec![40, 60, 98, 100]);
+        assert_eq!(composed.minimum(&SeedRange::new(30, 80)), Some((40, 0)));
+        assert_eq!(
+            composed.preimage(&SeedRange::new(5, 10)),
+            vec![
+                SeedRange { start: 5, end: 15 },
+                SeedRange { start: 45, end: 55 },
+                SeedRange {
+                    start: 98,
+                    end: 100
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_domain_end() {
+        let map = PiecewiseMap::from_maps(&[Map {
+            source: i64::MAX - 10,
+            destination: 0,
+            length: 10,
+        }]);
+        assert_eq!(map.apply(i64::MAX), i64::MAX);
+        assert_eq!(map.apply(i64::MAX - 1), 9);
+        let tail = SeedRange {
+            start: i64::MAX - 20,
+            end: i64::MAX,
+        };
+        assert_eq!(map.minimum(&tail), Some((i64::MAX - 10, 0)));
+        assert_eq!(
+            map.apply_range(&tail),
+            vec![
+                SeedRange {
+                    start: i64::MAX - 20,
+                    end: i64::MAX - 10
+                },
+                SeedRange { start: 0, end: 10 }
+            ]
+        );
+        assert_eq!(PiecewiseMap::identity().apply(i64::MAX), i64::MAX);
+    }
+}
@@ -29,6 +29,35 @@ impl SeedRange {
             end: dest + length,
         }
     }
+
+    pub fn intersect(&self, other: &SeedRange) -> Option<SeedRange> {
+        let start = i64::max(self.start, other.start);
+        let end = i64::min(self.end, other.end);
+        if start < end {
+            Some(SeedRange { start, end })
+        } else {
+            None
+        }
+    }
+
+    pub fn contains_range(&self, other: &SeedRange) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+}
+
+// Sorts the ranges and merges the overlapping or touching ones
+pub fn merge_ranges(mut seed_ranges: Vec<SeedRange>) -> Vec<SeedRange> {
+    seed_ranges.sort_by_key(|seed_range| seed_range.start);
+    let mut result: Vec<SeedRange> = Vec::new();
+    for seed_range in seed_ranges {
+        match result.last_mut() {
+            Some(last) if seed_range.start <= last.end => {
+                last.end = i64::max(last.end, seed_range.end)
+            }
+            _ => result.push(seed_range),
+        }
+    }
+    result
 }
 
 #[cfg(test)]
@@ -39,11 +68,28 @@ mod tests {
     fn test_seed_range() {
         let seed_range = SeedRange::new(79, 14);
 
-        assert!(seed_range.split_at(79).is_some());
+        assert_eq!(seed_range.split_at(79), vec![seed_range.clone()]);
         let split_at_80 = seed_range.split_at(80);
         assert_eq!(
             split_at_80,
-            Some(vec! {SeedRange{ start: 79, end: 80 }, SeedRange{start: 80, end: 93}})
+            vec! {SeedRange{ start: 79, end: 80 }, SeedRange{start: 80, end: 93}}
+        );
+    }
+
+    #[test]
+    fn test_merge_ranges() {
+        let merged = merge_ranges(vec![
+            SeedRange::new(10, 5),
+            SeedRange::new(0, 3),
+            SeedRange::new(3, 2),
+            SeedRange::new(12, 10),
+        ]);
+        assert_eq!(
+            merged,
+            vec![
+                SeedRange { start: 0, end: 5 },
+                SeedRange { start: 10, end: 22 }
+            ]
         );
     }
 
@@ -0,0 +1,260 @@
+use std::fmt;
+
+use crate::category_graph::{CategoryGraph, TransformStep};
+use crate::seed_range::{merge_ranges, SeedRange};
+
+#[derive(Debug, PartialEq)]
+pub enum Diagnostic {
+    OverlappingSources {
+        step: String,
+        first: usize,
+        second: usize,
+    },
+    ZeroLengthMap {
+        step: String,
+        index: usize,
+    },
+    NegativeLengthMap {
+        step: String,
+        index: usize,
+    },
+    ShiftOverflow {
+        step: String,
+        index: usize,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::OverlappingSources {
+                step,
+                first,
+                second,
+            } => write!(
+                f,
+                "{}: maps #{} and #{} have overlapping sources, the result depends on their order",
+                step, first, second
+            ),
+            Diagnostic::ZeroLengthMap { step, index } => {
+                write!(f, "{}: map #{} has zero length", step, index)
+            }
+            Diagnostic::NegativeLengthMap { step, index } => {
+                write!(f, "{}: map #{} has negative length", step, index)
+            }
+            Diagnostic::ShiftOverflow { step, index } => {
+                write!(f, "{}: map #{} overflows i64 when shifted", step, index)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StepCoverage {
+    pub step: String,
+    pub domain: Option<SeedRange>,
+    pub mapped: i64,
+    pub passthrough: i64,
+}
+
+impl StepCoverage {
+    pub fn passthrough_ratio(&self) -> f64 {
+        if self.mapped + self.passthrough == 0 {
+            return 0.0;
+        }
+        self.passthrough as f64 / (self.mapped + self.passthrough) as f64
+    }
+}
+
+#[derive(Debug)]
+pub struct ValidationReport {
+    pub diagnostics: Vec<Diagnostic>,
+    pub coverage: Vec<StepCoverage>,
+}
+
+fn step_name(step: &TransformStep) -> String {
+    format!("{}-to-{}", step.source, step.destination)
+}
+
+fn validate_step(step: &TransformStep, diagnostics: &mut Vec<Diagnostic>) -> StepCoverage {
+    let mut source_ranges = Vec::new();
+    // Only maps that passed every other check are compared for overlaps
+    let mut sound_maps: Vec<usize> = Vec::new();
+    for (index, map) in step.maps.iter().enumerate() {
+        if map.length < 0 {
+            diagnostics.push(Diagnostic::NegativeLengthMap {
+                step: step_name(step),
+                index,
+            });
+            continue;
+        }
+        if map.length == 0 {
+            diagnostics.push(Diagnostic::ZeroLengthMap {
+                step: step_name(step),
+                index,
+            });
+            continue;
+        }
+        let overflows = map.source.checked_add(map.length).is_none()
+            || map.destination.checked_add(map.length).is_none()
+            || map.destination.checked_sub(map.source).is_none();
+        if overflows {
+            diagnostics.push(Diagnostic::ShiftOverflow {
+                step: step_name(step),
+                index,
+            });
+            continue;
+        }
+        for &other_index in &sound_maps {
+            if step.maps[other_index].overlaps_with(&map.source_range()) {
+                diagnostics.push(Diagnostic::OverlappingSources {
+                    step: step_name(step),
+                    first: other_index,
+                    second: index,
+                });
+            }
+        }
+        sound_maps.push(index);
+        source_ranges.push(map.source_range());
+    }
+
+    let covered = merge_ranges(source_ranges);
+    let domain = match (covered.first(), covered.last()) {
+        (Some(first), Some(last)) => Some(SeedRange {
+            start: first.start,
+            end: last.end,
+        }),
+        _ => None,
+    };
+    let mapped: i64 = covered.iter().map(|range| range.end - range.start).sum();
+    let passthrough = domain
+        .as_ref()
+        .map_or(0, |domain| domain.end - domain.start - mapped);
+    StepCoverage {
+        step: step_name(step),
+        domain,
+        mapped,
+        passthrough,
+    }
+}
+
+pub fn validate(categories: &CategoryGraph) -> ValidationReport {
+    let mut diagnostics = Vec::new();
+    let coverage = categories
+        .steps()
+        .iter()
+        .map(|step| validate_step(step, &mut diagnostics))
+        .collect();
+    ValidationReport {
+        diagnostics,
+        coverage,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Map;
+
+    #[test]
+    fn test_validate() {
+        let categories = CategoryGraph::new(vec![TransformStep {
+            source: "seed".to_string(),
+            destination: "soil".to_string(),
+            maps: vec![
+                Map {
+                    source: 10,
+                    destination: 100,
+                    length: 10,
+                },
+                Map {
+                    source: 20,
+                    destination: 0,
+                    length: 5,
+                },
+                Map {
+                    source: 15,
+                    destination: 50,
+                    length: 10,
+                },
+                Map {
+                    source: 40,
+                    destination: 0,
+                    length: 0,
+                },
+                Map {
+                    source: 30,
+                    destination: i64::MAX,
+                    length: 10,
+                },
+            ],
+        }])
+        .unwrap();
+        let report = validate(&categories);
+        assert_eq!(
+            report.diagnostics,
+            vec![
+                Diagnostic::OverlappingSources {
+                    step: "seed-to-soil".to_string(),
+                    first: 0,
+                    second: 2
+                },
+                Diagnostic::OverlappingSources {
+                    step: "seed-to-soil".to_string(),
+                    first: 1,
+                    second: 2
+                },
+                Diagnostic::ZeroLengthMap {
+                    step: "seed-to-soil".to_string(),
+                    index: 3
+                },
+                Diagnostic::ShiftOverflow {
+                    step: "seed-to-soil".to_string(),
+                    index: 4
+                },
+            ]
+        );
+        assert_eq!(report.coverage[0].domain, Some(SeedRange::new(10, 15)));
+        assert_eq!(report.coverage[0].passthrough, 0);
+
+        // Broken maps are reported once and left out of the overlap check and the coverage
+        let categories = CategoryGraph::new(vec![TransformStep {
+            source: "seed".to_string(),
+            destination: "soil".to_string(),
+            maps: vec![
+                Map {
+                    source: i64::MAX - 1,
+                    destination: 0,
+                    length: 10,
+                },
+                Map {
+                    source: i64::MAX - 1,
+                    destination: 0,
+                    length: 1,
+                },
+                Map {
+                    source: 0,
+                    destination: 100,
+                    length: -5,
+                },
+            ],
+        }])
+        .unwrap();
+        let report = validate(&categories);
+        assert_eq!(
+            report.diagnostics,
+            vec![
+                Diagnostic::ShiftOverflow {
+                    step: "seed-to-soil".to_string(),
+                    index: 0
+                },
+                Diagnostic::NegativeLengthMap {
+                    step: "seed-to-soil".to_string(),
+                    index: 2
+                },
+            ]
+        );
+        assert_eq!(report.coverage[0].mapped, 1);
+        assert_eq!(report.coverage[0].passthrough, 0);
+    }
+}
@@ -1,19 +1,186 @@
 const EXAMPLE: &str = include_str!("example.txt");
 const INPUT: &str = include_str!("input.txt");
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BoatPhysics {
+    // mm/ms of speed gained for every ms spent charging
+    acceleration: u64,
+    max_speed: Option<u64>,
+    // mm/ms of speed lost for every ms spent travelling
+    friction: Option<u64>,
+}
+
+impl Default for BoatPhysics {
+    fn default() -> Self {
+        Self {
+            acceleration: 1,
+            max_speed: None,
+            friction: None,
+        }
+    }
+}
+
+impl BoatPhysics {
+    fn speed_for_charge(&self, charging_time: u64) -> u128 {
+        let speed = self.acceleration as u128 * charging_time as u128;
+        self.max_speed
+            .map_or(speed, |max_speed| u128::min(speed, max_speed as u128))
+    }
+
+    fn distance(&self, charging_time: u64, race_time: u64) -> u128 {
+        let speed = self.speed_for_charge(charging_time);
+        let travel_time = (race_time - charging_time) as u128;
+        match self.friction.filter(|friction| *friction > 0) {
+            None => speed.saturating_mul(travel_time),
+            Some(friction) => {
+                let friction = friction as u128;
+                let moving_time = u128::min(travel_time, speed.div_ceil(friction));
+                moving_time.saturating_mul(speed)
+                    - friction * moving_time * moving_time.saturating_sub(1) / 2
+            }
+        }
+    }
+
+    // The first charging time at which the speed cap is reached
+    fn saturation_charge(&self) -> Option<u64> {
+        self.max_speed
+            .map(|max_speed| max_speed.div_ceil(self.acceleration))
+    }
+}
+
+// Smallest value in lo..=hi for which the monotone predicate holds, the predicate must hold for hi
+fn first_where(mut lo: u64, mut hi: u64, predicate: impl Fn(u64) -> bool) -> u64 {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if predicate(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    lo
+}
+
 #[derive(Debug)]
 struct RaceData {
     distance: u64,
     time: u64,
+    physics: BoatPhysics,
 }
 
 impl RaceData {
-    fn get_distances_for_time(&self) -> Vec<u64> {
-        let mut res = Vec::new();
-        for charging_time in 0..(self.time + 1) {
-            res.push((self.time - charging_time) * charging_time)
+    fn new(time: u64, distance: u64) -> Self {
+        Self {
+            distance,
+            time,
+            physics: BoatPhysics::default(),
         }
-        return res;
+    }
+
+    fn with_physics(self, physics: BoatPhysics) -> Self {
+        Self { physics, ..self }
+    }
+
+    fn wins_with_charge(&self, charging_time: u64) -> bool {
+        self.physics.distance(charging_time, self.time) > self.distance as u128
+    }
+
+    fn has_closed_form(&self) -> bool {
+        let acceleration_times_time = self.physics.acceleration as u128 * self.time as u128;
+        let four_acceleration_times_distance =
+            (self.physics.acceleration as u128 * self.distance as u128).checked_mul(4);
+        self.physics.friction.unwrap_or(0) == 0
+            && self.physics.acceleration > 0
+            && acceleration_times_time
+                .checked_mul(acceleration_times_time)
+                .is_some()
+            && four_acceleration_times_distance.is_some()
+    }
+
+    fn winning_interval(&self) -> Option<(u64, u64)> {
+        if self.physics.acceleration == 0 {
+            return None;
+        }
+        if self.has_closed_form() {
+            self.closed_form_interval()
+        } else {
+            self.searched_interval()
+        }
+    }
+
+    // Before the speed cap, charging times c with a * c * (time - c) > distance lie strictly between
+    // the roots of a * c^2 - a * time * c + distance = 0, the isqrt guess is only nudged to the exact boundary.
+    fn quadratic_interval(&self) -> Option<(u64, u64)> {
+        let acceleration = self.physics.acceleration as u128;
+        let time = self.time as u128;
+        let wins = |charging_time: u64| {
+            acceleration * charging_time as u128 * (self.time - charging_time) as u128
+                > self.distance as u128
+        };
+        let discriminant = (acceleration * time)
+            .pow(2)
+            .checked_sub(4 * acceleration * self.distance as u128)?;
+        let mut lo = ((acceleration * time - discriminant.isqrt()) / (2 * acceleration)) as u64;
+        while lo > 0 && wins(lo - 1) {
+            lo -= 1;
+        }
+        while lo <= self.time / 2 && !wins(lo) {
+            lo += 1;
+        }
+        if lo > self.time / 2 {
+            return None;
+        }
+        Some((lo, self.time - lo))
+    }
+
+    fn closed_form_interval(&self) -> Option<(u64, u64)> {
+        // None when the boat never reaches its top speed within the race
+        let saturation_charge = self
+            .physics
+            .saturation_charge()
+            .filter(|charge| *charge <= self.time);
+        let uncapped = match saturation_charge {
+            None => self.quadratic_interval(),
+            Some(saturation_charge) => self
+                .quadratic_interval()
+                .filter(|(lo, _)| *lo < saturation_charge)
+                .map(|(lo, hi)| (lo, u64::min(hi, saturation_charge - 1))),
+        };
+        // Once capped the distance max_speed * (time - c) only shrinks
+        let capped = self.physics.max_speed.and_then(|max_speed| {
+            if max_speed == 0 {
+                return None;
+            }
+            let hi = self.time.checked_sub(self.distance / max_speed + 1)?;
+            let saturation_charge = saturation_charge?;
+            (saturation_charge <= hi).then_some((saturation_charge, hi))
+        });
+        match (uncapped, capped) {
+            (Some((lo, _)), Some((_, hi))) => Some((lo, hi)),
+            (uncapped, capped) => uncapped.or(capped),
+        }
+    }
+
+    // Assumes the distance first grows and then shrinks with the charging time
+    fn searched_interval(&self) -> Option<(u64, u64)> {
+        let distance = |charging_time: u64| self.physics.distance(charging_time, self.time);
+        let peak = first_where(0, self.time, |charging_time| {
+            charging_time == self.time || distance(charging_time) >= distance(charging_time + 1)
+        });
+        if !self.wins_with_charge(peak) {
+            return None;
+        }
+        let lo = first_where(0, peak, |charging_time| {
+            self.wins_with_charge(charging_time)
+        });
+        let hi = first_where(peak, self.time, |charging_time| {
+            charging_time == self.time || !self.wins_with_charge(charging_time + 1)
+        });
+        Some((lo, hi))
+    }
+
+    fn number_of_ways_to_win(&self) -> u64 {
+        self.winning_interval().map_or(0, |(lo, hi)| hi - lo + 1)
     }
 }
 
@@ -34,26 +201,92 @@ fn parse_race_datas(input_str: &str) -> Vec<RaceData> {
     let races: Vec<RaceData> = distances
         .into_iter()
         .zip(times)
-        .map(|(distance, time)| RaceData { distance, time })
+        .map(|(distance, time)| RaceData::new(time, distance))
         .collect();
 
     return races;
 }
 
+fn parse_kerned_line(line: &str) -> Result<u64, &'static str> {
+    let (_, values) = line.split_once(':').ok_or("Missing label")?;
+    values
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .parse()
+        .map_err(|_| "Invalid kerned number")
+}
+
+fn parse_kerned_race(input_str: &str) -> Result<RaceData, &'static str> {
+    let mut lines = input_str.lines();
+    let time = parse_kerned_line(lines.next().ok_or("No time line")?)?;
+    let distance = parse_kerned_line(lines.next().ok_or("No distance line")?)?;
+    Ok(RaceData::new(time, distance))
+}
+
 fn star_one(input_str: &str) -> u64 {
     let races = parse_race_datas(input_str);
     let mut sum: u64 = 1;
     for race in &races {
-        let possible_dists = race.get_distances_for_time();
-        let count: u64 = possible_dists
-            .iter()
-            .filter(|&&x| x > race.distance)
-            .count() as u64;
-        sum = sum * count;
+        sum *= race.number_of_ways_to_win();
     }
     return sum;
 }
 
+fn star_two(input_str: &str) -> u64 {
+    parse_kerned_race(input_str)
+        .unwrap()
+        .number_of_ways_to_win()
+}
+
 fn main() {
-    println!("{:?}", star_one(INPUT));
+    const SPACED_EXAMPLE: &str = "Time:      7  15   30\nDistance:  9  40  200";
+    assert_eq!(star_one(SPACED_EXAMPLE), 288);
+    assert_eq!(star_two(SPACED_EXAMPLE), 71503);
+    assert_eq!(star_two(EXAMPLE), 71503);
+
+    // The boundary is strict: charging 10 ms in a 20 ms race only ties the record
+    let tied_race = RaceData::new(20, 100);
+    assert_eq!(tied_race.winning_interval(), None);
+    let race = RaceData::new(30, 200);
+    assert_eq!(race.winning_interval(), Some((11, 19)));
+    let longest_race = RaceData::new(u64::MAX, u64::MAX);
+    assert_eq!(longest_race.winning_interval(), Some((2, u64::MAX - 2)));
+    // 4 * acceleration * distance does not fit the quadratic, so the interval is searched
+    let hopeless_race = RaceData::new(1, u64::MAX).with_physics(BoatPhysics {
+        acceleration: u64::MAX,
+        ..BoatPhysics::default()
+    });
+    assert_eq!(hopeless_race.winning_interval(), None);
+
+    let variants = [
+        BoatPhysics {
+            acceleration: 3,
+            max_speed: None,
+            friction: None,
+        },
+        BoatPhysics {
+            acceleration: 2,
+            max_speed: Some(25),
+            friction: None,
+        },
+        BoatPhysics {
+            acceleration: 2,
+            max_speed: Some(40),
+            friction: Some(1),
+        },
+    ];
+    for physics in variants {
+        for race in parse_race_datas(SPACED_EXAMPLE) {
+            let race = race.with_physics(physics);
+            let winning: Vec<u64> = (0..=race.time)
+                .filter(|charging_time| race.wins_with_charge(*charging_time))
+                .collect();
+            let expected = winning.first().map(|lo| (*lo, *winning.last().unwrap()));
+            assert_eq!(race.winning_interval(), expected, "{:?}", race);
+            println!("{:?}: {:?}", race, race.winning_interval());
+        }
+    }
+
+    println!("{:?} {:?}", star_one(INPUT), star_two(INPUT));
 }
@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::rules::Rules;
+use crate::{rank_hands, HandSignature};
+
+#[derive(Debug, Serialize)]
+pub struct HandExplanation {
+    pub hand: String,
+    pub hand_type: HandSignature,
+    pub substitution: Option<char>,
+    pub rank: u32,
+    pub bid: u32,
+    pub winnings: u64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct HandTypeStats {
+    pub hands: usize,
+    pub total_bid: u64,
+    pub total_winnings: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Explanation {
+    pub hands: Vec<HandExplanation>,
+    pub type_distribution: BTreeMap<HandSignature, HandTypeStats>,
+    pub total_winnings: u64,
+}
+
+pub fn explain(input: &str, rules: &Rules) -> Explanation {
+    let mut type_distribution: BTreeMap<HandSignature, HandTypeStats> = BTreeMap::new();
+    let mut hands = Vec::new();
+    for (rank, game_data) in (1..).zip(rank_hands(input, rules)) {
+        let winnings = game_data.bid as u64 * rank as u64;
+        let stats = type_distribution
+            .entry(game_data.camel_rank.clone())
+            .or_default();
+        stats.hands += 1;
+        stats.total_bid += game_data.bid as u64;
+        stats.total_winnings += winnings;
+        hands.push(HandExplanation {
+            hand: game_data.hand,
+            hand_type: game_data.camel_rank,
+            substitution: game_data.substitution,
+            rank,
+            bid: game_data.bid,
+            winnings,
+        });
+    }
+    Explanation {
+        total_winnings: hands.iter().map(|hand| hand.winnings).sum(),
+        hands,
+        type_distribution,
+    }
+}
+
+impl Explanation {
+    pub fn to_table(&self) -> String {
+        let mut table = format!(
+            "{:>5} {:<8} {:<14} {:<12} {:>6} {:>10}\n",
+            "rank", "hand", "type", "substitution", "bid", "winnings"
+        );
+        for hand in &self.hands {
+            let substitution = hand.substitution.map_or("-".to_string(), |c| c.to_string());
+            table += &format!(
+                "{:>5} {:<8} {:<14} {:<12} {:>6} {:>10}\n",
+                hand.rank,
+                hand.hand,
+                hand.hand_type.to_string(),
+                substitution,
+                hand.bid,
+                hand.winnings
+            );
+        }
+        table += "\n";
+        for (hand_type, stats) in &self.type_distribution {
+            table += &format!(
+                "{:<14} {:>5} hands {:>10} bid {:>12} winnings\n",
+                hand_type.to_string(),
+                stats.hands,
+                stats.total_bid,
+                stats.total_winnings
+            );
+        }
+        table += &format!("Total winnings: {}", self.total_winnings);
+        table
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
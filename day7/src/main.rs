@@ -1,67 +1,121 @@
+mod explain;
+mod rules;
+
+use crate::explain::explain;
+use crate::rules::Rules;
+use serde::{Serialize, Serializer};
 use std::collections::HashMap;
+use std::fmt;
 
 const EXAMPLE: &str = include_str!("example.txt");
 const INPUT: &str = include_str!("input.txt");
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    FullHouse,
+    FourOfAKind,
+    FiveOfAKind,
+}
+
+// How many times each card occurs in the hand, most frequent first. Comparing these
+// lexicographically orders hands of any size, for five cards it matches the HandType order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct HandSignature(Vec<u32>);
+
+impl HandSignature {
+    fn hand_type(&self) -> Option<HandType> {
+        match self.0.as_slice() {
+            [5] => Some(HandType::FiveOfAKind),
+            [4, 1] => Some(HandType::FourOfAKind),
+            [3, 2] => Some(HandType::FullHouse),
+            [3, 1, 1] => Some(HandType::ThreeOfAKind),
+            [2, 2, 1] => Some(HandType::TwoPair),
+            [2, 1, 1, 1] => Some(HandType::OnePair),
+            [1, 1, 1, 1, 1] => Some(HandType::HighCard),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HandSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.hand_type() {
+            Some(hand_type) => write!(f, "{:?}", hand_type),
+            None => {
+                let multiplicities: Vec<String> = self.0.iter().map(|m| m.to_string()).collect();
+                write!(f, "{}", multiplicities.join("+"))
+            }
+        }
+    }
+}
+
+impl Serialize for HandSignature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 #[derive(Debug)]
 struct GameData {
+    hand: String,
     cards: Vec<u32>,
     bid: u32,
-    camel_rank: u32,
+    camel_rank: HandSignature,
+    substitution: Option<char>,
 }
 
 fn find_max_key<K, V>(a_hash_map: &HashMap<K, V>) -> Option<&K>
 where
+    K: Ord,
     V: Ord,
 {
     a_hash_map
         .iter()
-        .max_by(|a, b| a.1.cmp(&b.1))
+        .max_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)))
         .map(|(k, _v)| k)
 }
 
-fn get_camel_rank(cards: &Vec<u32>) -> u32 {
+// Returns the hand signature along with the card the wildcards pretended to be
+fn get_camel_rank(
+    hand: &str,
+    rules: &Rules,
+) -> Result<(HandSignature, Option<char>), &'static str> {
     let mut card_rarity: HashMap<u32, u32> = HashMap::new();
+    let mut num_of_wildcards = 0;
 
-    for &card in cards {
-        *card_rarity.entry(card).or_insert(0) += 1;
-    }
-    if card_rarity.len() == 1 {
-        return 7;
-    }
-
-    let num_of_jokers = card_rarity.remove(&1).unwrap_or(0);
-    let max_value_key = find_max_key(&card_rarity).unwrap();
-    let curr_value = card_rarity.get(&max_value_key).unwrap();
-    card_rarity.insert(*max_value_key, *curr_value + num_of_jokers);
-
-    if card_rarity.values().any(|&x| x == 5) {
-        return 7;
+    for card in hand.chars() {
+        if rules.wildcards_upgrade && rules.is_wild(card) {
+            num_of_wildcards += 1;
+        } else {
+            *card_rarity.entry(rules.card_value(card)?).or_insert(0) += 1;
+        }
     }
-
-    if card_rarity.values().any(|&x| x == 4) {
-        return 6;
+    if num_of_wildcards == 0 && card_rarity.is_empty() {
+        return Err("A hand must have at least one card");
     }
-
-    if card_rarity.values().any(|&x| x == 3) {
-        if card_rarity.values().any(|&x| x == 2) {
-            return 5;
+    let substitution = match find_max_key(&card_rarity).copied() {
+        Some(max_value_key) => {
+            *card_rarity.get_mut(&max_value_key).unwrap() += num_of_wildcards;
+            rules.card_order[max_value_key as usize]
         }
-        return 4;
-    }
+        None => {
+            let strongest = rules.card_order.iter().rev().find(|c| !rules.is_wild(**c));
+            return Ok((HandSignature(vec![num_of_wildcards]), strongest.copied()));
+        }
+    };
+    let substitution = (num_of_wildcards > 0).then_some(substitution);
 
-    match card_rarity.len() {
-        3 => 3,
-        4 => 2,
-        5 => 1,
-        _ => panic!("Should be something else"),
-    }
+    let mut multiplicities: Vec<u32> = card_rarity.into_values().collect();
+    multiplicities.sort_by(|a, b| b.cmp(a));
+    Ok((HandSignature(multiplicities), substitution))
 }
 
-impl std::str::FromStr for GameData {
-    type Err = &'static str;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl GameData {
+    fn parse(s: &str, rules: &Rules) -> Result<Self, &'static str> {
         let parts: Vec<&str> = s.split_whitespace().collect();
 
         if parts.len() != 2 {
@@ -71,14 +125,19 @@ impl std::str::FromStr for GameData {
         let card_str = parts[0];
         let bid_str = parts[1];
 
-        let cards: Vec<u32> = card_str.chars().map(|c| convert_char_to_value(c)).collect();
+        let cards: Vec<u32> = card_str
+            .chars()
+            .map(|c| rules.card_value(c))
+            .collect::<Result<Vec<u32>, &'static str>>()?;
         let bid: u32 = bid_str.parse().map_err(|_| "Failed to parse bid")?;
-        let camel_rank = get_camel_rank(&cards);
+        let (camel_rank, substitution) = get_camel_rank(card_str, rules)?;
 
         Ok(GameData {
+            hand: card_str.to_string(),
             cards,
             bid,
             camel_rank,
+            substitution,
         })
     }
 }
@@ -107,27 +166,21 @@ impl Ord for GameData {
     }
 }
 
-fn convert_char_to_value(c: char) -> u32 {
-    match c {
-        '2'..='9' => c.to_digit(10).unwrap(),
-        'T' => 10,
-        'J' => 1,
-        'Q' => 12,
-        'K' => 13,
-        'A' => 14,
-        _ => panic!("Invalid character in card string"),
-    }
-}
-
-fn star_one(input: &str) -> u32 {
+// Weakest hand first
+fn rank_hands(input: &str, rules: &Rules) -> Vec<GameData> {
     let mut cards = Vec::new();
     for line in input.lines() {
-        match line.parse::<GameData>() {
+        match GameData::parse(line, rules) {
             Ok(data) => cards.push(data),
             Err(err) => eprintln!("Error parsing line: {}", err),
         }
     }
     cards.sort();
+    cards
+}
+
+fn total_winnings(input: &str, rules: &Rules) -> u32 {
+    let cards = rank_hands(input, rules);
     let mut i = 1;
     let mut sum = 0;
     for card in &cards {
@@ -137,6 +190,46 @@ fn star_one(input: &str) -> u32 {
     return sum;
 }
 
+fn star_one(input: &str) -> u32 {
+    total_winnings(input, &Rules::standard())
+}
+
+fn star_two(input: &str) -> u32 {
+    total_winnings(input, &Rules::jokers())
+}
+
 fn main() {
-    println!("{:?}", star_one(INPUT));
+    assert_eq!(star_one(EXAMPLE), 6440);
+    assert_eq!(star_two(EXAMPLE), 5905);
+
+    let two_jokers = Rules::new("WJ23456789TQKA", "WJ", true);
+    assert_eq!(
+        get_camel_rank("WJ2K3", &two_jokers),
+        Ok((HandSignature(vec![3, 1, 1]), Some('K')))
+    );
+    let ace_low = Rules::new("A23456789TJQK", "", false);
+    assert!(
+        GameData::parse("A2345 1", &ace_low).unwrap()
+            < GameData::parse("23456 1", &ace_low).unwrap()
+    );
+    println!("Ace low: {:?}", total_winnings(EXAMPLE, &ace_low));
+
+    let six_card_hands = "AAAKKK 10\nAAAAKQ 20\nAAAAAA 30\nQQKKAA 40";
+    let six_card_ranks: Vec<String> = rank_hands(six_card_hands, &Rules::standard())
+        .iter()
+        .map(|game_data| game_data.camel_rank.to_string())
+        .collect();
+    assert_eq!(six_card_ranks, vec!["2+2+2", "3+3", "4+1+1", "6"]);
+    let double_deck = Rules::new("23456789TJQKA", "J", true);
+    assert_eq!(
+        get_camel_rank("AAAAJJA", &double_deck),
+        Ok((HandSignature(vec![7]), Some('A')))
+    );
+
+    let example_explanation = explain(EXAMPLE, &Rules::jokers());
+    assert_eq!(example_explanation.total_winnings, 5905);
+    println!("{}", example_explanation.to_table());
+    println!("{}", example_explanation.to_json());
+
+    println!("{:?} {:?}", star_one(INPUT), star_two(INPUT));
 }
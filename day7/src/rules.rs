@@ -0,0 +1,38 @@
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rules {
+    // Weakest card first
+    pub card_order: Vec<char>,
+    pub wildcards: Vec<char>,
+    // Whether wildcards pretend to be whatever card makes the hand type strongest
+    pub wildcards_upgrade: bool,
+}
+
+impl Rules {
+    pub fn new(card_order: &str, wildcards: &str, wildcards_upgrade: bool) -> Self {
+        Self {
+            card_order: card_order.chars().collect(),
+            wildcards: wildcards.chars().collect(),
+            wildcards_upgrade,
+        }
+    }
+
+    pub fn standard() -> Self {
+        Self::new("23456789TJQKA", "", false)
+    }
+
+    pub fn jokers() -> Self {
+        Self::new("J23456789TQKA", "J", true)
+    }
+
+    pub fn card_value(&self, card: char) -> Result<u32, &'static str> {
+        self.card_order
+            .iter()
+            .position(|c| *c == card)
+            .map(|position| position as u32)
+            .ok_or("Card is not part of the card order")
+    }
+
+    pub fn is_wild(&self, card: char) -> bool {
+        self.wildcards.contains(&card)
+    }
+}
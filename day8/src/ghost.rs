@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use crate::network::Network;
+
+// A ghost's walk is a tail followed by a cycle that repeats forever. Steps are counted from
+// the start node, so hits are step numbers at which the ghost stands on a goal node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GhostCycle {
+    pub start: usize,
+    pub tail_length: u64,
+    pub period: u64,
+    pub tail_hits: Vec<u64>,
+    // Every hit in tail_length..tail_length + period, it repeats with the period
+    pub cycle_hits: Vec<u64>,
+}
+
+impl GhostCycle {
+    pub fn analyse(network: &Network, start: usize, is_goal: &impl Fn(&str) -> bool) -> Self {
+        let instruction_length = network.instructions.len() as u64;
+        let mut first_seen: HashMap<(usize, u64), u64> = HashMap::new();
+        let mut hits = Vec::new();
+        let mut node = start;
+        let mut step = 0;
+        let tail_length = loop {
+            if let Some(seen_at) = first_seen.insert((node, step % instruction_length), step) {
+                break seen_at;
+            }
+            if is_goal(&network.names[node]) {
+                hits.push(step);
+            }
+            node = network.step(node, step);
+            step += 1;
+        };
+        let (tail_hits, cycle_hits) = hits.into_iter().partition(|hit| *hit < tail_length);
+        GhostCycle {
+            start,
+            tail_length,
+            period: step - tail_length,
+            tail_hits,
+            cycle_hits,
+        }
+    }
+}
+
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+// Merges x = a1 (mod m1) and x = a2 (mod m2) into x = a (mod lcm(m1, m2)), the moduli need not be coprime
+pub fn crt(a1: i128, m1: i128, a2: i128, m2: i128) -> Option<(i128, i128)> {
+    let (g, p, _) = extended_gcd(m1, m2);
+    if (a2 - a1) % g != 0 {
+        return None;
+    }
+    let modulus = m1 / g * m2;
+    let k = ((a2 - a1) / g * p).rem_euclid(m2 / g);
+    Some(((a1 + m1 * k).rem_euclid(modulus), modulus))
+}
+
+pub fn earliest_common_step(
+    network: &Network,
+    ghosts: &[GhostCycle],
+    is_goal: &impl Fn(&str) -> bool,
+) -> Option<u64> {
+    let longest_tail = ghosts.iter().map(|ghost| ghost.tail_length).max()?;
+
+    // Before every ghost entered its cycle just walk them together
+    let mut nodes: Vec<usize> = ghosts.iter().map(|ghost| ghost.start).collect();
+    for step in 0..longest_tail {
+        if nodes.iter().all(|node| is_goal(&network.names[*node])) {
+            return Some(step);
+        }
+        for node in nodes.iter_mut() {
+            *node = network.step(*node, step);
+        }
+    }
+
+    // Afterwards every combination of cycle hits is a system of congruences
+    let mut residues: Vec<(i128, i128)> = vec![(0, 1)];
+    for ghost in ghosts {
+        residues = residues
+            .iter()
+            .flat_map(|(a, m)| {
+                ghost
+                    .cycle_hits
+                    .iter()
+                    .filter_map(move |hit| crt(*a, *m, *hit as i128, ghost.period as i128))
+            })
+            .collect();
+    }
+    residues
+        .into_iter()
+        .map(|(a, m)| {
+            let behind = (longest_tail as i128 - a).max(0);
+            a + (behind + m - 1) / m * m
+        })
+        .min()
+        .and_then(|step| u64::try_from(step).ok())
+}
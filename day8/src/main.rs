@@ -1,5 +1,8 @@
+mod ghost;
+mod network;
+
+use crate::network::{Network, NodePredicate};
 use regex::Regex;
-use std::collections::HashMap;
 
 const EXAMPLE: &str = include_str!("example.txt");
 const EXAMPLE_2: &str = include_str!("example_2.txt");
@@ -31,130 +34,81 @@ fn parse_line(line: &str) -> Option<(String, Node)> {
     return None;
 }
 
-fn star_one(input: &str) -> u32 {
-    let mut lines = input.lines();
-    let instructions = lines.next().unwrap();
-    let mut map = HashMap::new();
-    for line in lines {
-        if line.is_empty() {
-            continue;
-        }
-        if let Some((node_id, node)) = parse_line(line) {
-            map.insert(node_id, node);
-        } else {
-            panic!("no nodes")
-        }
-    }
-    let mut steps = 0;
-    let mut curr_node = "AAA".to_string();
-    let mut instructions = instructions.chars().cycle();
-    while curr_node != "ZZZ" {
-        match instructions.next().unwrap() {
-            'L' => curr_node = map.get(&curr_node).unwrap().left.clone(),
-            'R' => curr_node = map.get(&curr_node).unwrap().right.clone(),
-            _ => {
-                panic!("Invalid instruction")
-            }
-        }
-        steps = steps + 1;
-    }
-    return steps;
-}
-
-fn gcd(a: i64, b: i64) -> i64 {
-    if b == 0 {
-        a
-    } else {
-        gcd(b, a % b)
-    }
-}
-
-fn lcm(a: i64, b: i64) -> i64 {
-    if a == 0 || b == 0 {
-        0
-    } else {
-        a * b / gcd(a, b)
-    }
+fn star_one(input: &str) -> u64 {
+    let network = Network::parse(input).unwrap();
+    network
+        .steps_to_goal(
+            &NodePredicate::Name("AAA".to_string()),
+            &NodePredicate::Name("ZZZ".to_string()),
+        )
+        .unwrap()
 }
 
 fn done(curr_nodes: &Vec<String>) -> bool {
     return curr_nodes.iter().all(|s| s.ends_with('Z'));
 }
 
-fn star_two(input: &str) -> i64 {
-    let mut lines = input.lines();
-    let instructions = lines.next().unwrap();
-    let mut map = HashMap::new();
-    let mut start_nodes = Vec::new();
-    for line in lines {
-        if line.is_empty() {
-            continue;
-        }
-        if let Some((node_id, node)) = parse_line(line) {
-            if node_id.ends_with('A') {
-                start_nodes.push(node_id.clone());
-            }
-            map.insert(node_id, node);
-        } else {
-            panic!("no nodes")
-        }
-    }
+fn star_two(input: &str) -> u64 {
+    let network = Network::parse(input).unwrap();
+    network
+        .steps_to_goal(
+            &NodePredicate::Suffix("A".to_string()),
+            &NodePredicate::Suffix("Z".to_string()),
+        )
+        .unwrap()
+}
 
-    let mut good_coords_map = HashMap::new();
-    for start_node in start_nodes {
-        let mut coords = vec![start_node.clone()];
+fn main() {
+    assert_eq!(star_one(EXAMPLE), 2);
+    assert_eq!(star_one(EXAMPLE_2), 6);
+    assert_eq!(star_two(EXAMPLE_3), 6);
 
-        let mut good_coords = Vec::new();
-        let mut steps = 0;
-        let mut is_loop = false;
-        let instruction_length = instructions.len() as i64;
-        let mut instructions = instructions.chars().cycle();
-        while !is_loop {
-            match instructions.next().unwrap() {
-                'L' => coords.push(
-                    map.get(&coords.last().unwrap().clone())
-                        .unwrap()
-                        .left
-                        .clone(),
-                ),
-                'R' => coords.push(
-                    map.get(&coords.last().unwrap().clone())
-                        .unwrap()
-                        .right
-                        .clone(),
-                ),
-                _ => {
-                    panic!("Invalid instruction")
-                }
-            }
-            if coords.last().unwrap().ends_with('Z') {
-                good_coords.push(steps + 1);
-            }
+    // A ghost with a tail and an offset goal, the lcm of the first hits (3 and 4) would be 12
+    let offset_ghosts = [
+        "L",
+        "",
+        "11A = (11B, 11B)",
+        "11B = (11C, 11C)",
+        "11C = (11Z, 11Z)",
+        "11Z = (11B, 11B)",
+        "22A = (22B, 22B)",
+        "22B = (22C, 22C)",
+        "22C = (22D, 22D)",
+        "22D = (22Z, 22Z)",
+        "22Z = (22E, 22E)",
+        "22E = (22F, 22F)",
+        "22F = (22G, 22G)",
+        "22G = (22H, 22H)",
+        "22H = (22Z, 22Z)",
+    ]
+    .join("\n");
+    assert_eq!(star_two(&offset_ghosts), 9);
 
-            let mut last_cycle = (coords.len() as i64) - 1 - instruction_length;
-            while last_cycle > 0 {
-                let last_cycle_coord = coords.get(last_cycle as usize).unwrap();
-                let curr_coord = coords.last().unwrap();
-                if *last_cycle_coord == *curr_coord {
-                    is_loop = true;
-                    good_coords.retain(|&x| x >= last_cycle);
-                }
-                last_cycle = last_cycle - instruction_length;
-            }
-            steps += 1;
-        }
-        good_coords_map.insert(start_node, good_coords);
+    let example_3_network = Network::parse(EXAMPLE_3).unwrap();
+    let starts = NodePredicate::pattern("^..A$").unwrap();
+    let goals = NodePredicate::pattern("Z$").unwrap();
+    for ghost in example_3_network.analyse(&starts, &goals) {
+        println!(
+            "{}: tail {}, period {}, goals in tail {:?}, goals in cycle {:?}",
+            example_3_network.names[ghost.start],
+            ghost.tail_length,
+            ghost.period,
+            ghost.tail_hits,
+            ghost.cycle_hits
+        );
     }
-    println!("{:?}", good_coords_map);
-    let mut values_to_fold = Vec::new();
-    for (_, good_coords) in good_coords_map {
-        values_to_fold.push(good_coords[0]);
-    }
-    let result = values_to_fold.iter().cloned().fold(1, lcm);
+    assert!(example_3_network
+        .steps_to_goal(&starts, &NodePredicate::Name("XXX".to_string()))
+        .is_err());
 
-    return result;
-}
+    if std::env::args().any(|arg| arg == "--dot") {
+        let network = Network::parse(INPUT).unwrap();
+        let dot = network.to_dot(
+            &NodePredicate::Suffix("A".to_string()),
+            &NodePredicate::Suffix("Z".to_string()),
+        );
+        std::fs::write("network.dot", dot).expect("Error writing to DOT file");
+    }
 
-fn main() {
-    println!("Example: {}", star_two(INPUT));
+    println!("Input: {} {}", star_one(INPUT), star_two(INPUT));
 }
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use petgraph::dot::Dot;
+use petgraph::graph::NodeIndex;
+use petgraph::Graph;
+use regex::Regex;
+
+use crate::ghost::{earliest_common_step, GhostCycle};
+use crate::parse_line;
+
+#[derive(Debug)]
+pub enum NodePredicate {
+    Name(String),
+    Suffix(String),
+    Pattern(Regex),
+}
+
+impl NodePredicate {
+    pub fn pattern(pattern: &str) -> Result<Self, &'static str> {
+        Ok(NodePredicate::Pattern(
+            Regex::new(pattern).map_err(|_| "Invalid node pattern")?,
+        ))
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NodePredicate::Name(node_name) => name == node_name,
+            NodePredicate::Suffix(suffix) => name.ends_with(suffix.as_str()),
+            NodePredicate::Pattern(regex) => regex.is_match(name),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Network {
+    pub instructions: Vec<char>,
+    pub names: Vec<String>,
+    left: Vec<usize>,
+    right: Vec<usize>,
+}
+
+impl Network {
+    pub fn parse(input: &str) -> Result<Self, &'static str> {
+        let mut lines = input.lines();
+        let instructions: Vec<char> = lines.next().ok_or("No instructions")?.chars().collect();
+        if instructions.is_empty() || instructions.iter().any(|c| *c != 'L' && *c != 'R') {
+            return Err("Invalid instruction");
+        }
+
+        let mut nodes = Vec::new();
+        for line in lines {
+            if line.is_empty() {
+                continue;
+            }
+            nodes.push(parse_line(line).ok_or("Invalid node")?);
+        }
+        let index_of: HashMap<String, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, (name, _))| (name.clone(), index))
+            .collect();
+        let lookup = |name: &String| index_of.get(name).copied().ok_or("Unknown node");
+        let left = nodes
+            .iter()
+            .map(|(_, node)| lookup(&node.left))
+            .collect::<Result<Vec<usize>, &'static str>>()?;
+        let right = nodes
+            .iter()
+            .map(|(_, node)| lookup(&node.right))
+            .collect::<Result<Vec<usize>, &'static str>>()?;
+
+        Ok(Self {
+            instructions,
+            names: nodes.into_iter().map(|(name, _)| name).collect(),
+            left,
+            right,
+        })
+    }
+
+    pub fn nodes_matching(&self, predicate: &NodePredicate) -> Vec<usize> {
+        (0..self.names.len())
+            .filter(|node| predicate.matches(&self.names[*node]))
+            .collect()
+    }
+
+    pub fn analyse(&self, start: &NodePredicate, goal: &NodePredicate) -> Vec<GhostCycle> {
+        self.nodes_matching(start)
+            .into_iter()
+            .map(|start_node| GhostCycle::analyse(self, start_node, &|name| goal.matches(name)))
+            .collect()
+    }
+
+    // Walks from every start node at once until all of them stand on a goal node
+    pub fn steps_to_goal(
+        &self,
+        start: &NodePredicate,
+        goal: &NodePredicate,
+    ) -> Result<u64, &'static str> {
+        let ghosts = self.analyse(start, goal);
+        if ghosts.is_empty() {
+            return Err("No start node");
+        }
+        if ghosts
+            .iter()
+            .any(|ghost| ghost.tail_hits.is_empty() && ghost.cycle_hits.is_empty())
+        {
+            return Err("A goal node is unreachable from a start node");
+        }
+        earliest_common_step(self, &ghosts, &|name| goal.matches(name))
+            .ok_or("The start nodes never reach goal nodes at the same step")
+    }
+
+    // Edge labels tell on how many steps of one instruction cycle the edge is taken,
+    // start nodes are green and goal nodes are red
+    pub fn to_dot(&self, start: &NodePredicate, goal: &NodePredicate) -> String {
+        let instruction_length = self.instructions.len();
+        let lefts = self.instructions.iter().filter(|c| **c == 'L').count();
+        let rights = instruction_length - lefts;
+
+        let mut graph: Graph<String, String> = Graph::new();
+        let indices: Vec<NodeIndex> = self
+            .names
+            .iter()
+            .map(|name| graph.add_node(name.clone()))
+            .collect();
+        for node in 0..self.names.len() {
+            if self.left[node] == self.right[node] {
+                graph.add_edge(indices[node], indices[self.left[node]], "L R".to_string());
+            } else {
+                let left_label = format!("L {}/{}", lefts, instruction_length);
+                let right_label = format!("R {}/{}", rights, instruction_length);
+                graph.add_edge(indices[node], indices[self.left[node]], left_label);
+                graph.add_edge(indices[node], indices[self.right[node]], right_label);
+            }
+        }
+
+        let dot = format!(
+            "{:?}",
+            Dot::with_attr_getters(&graph, &[], &|_, _| String::new(), &|_, (_, name)| {
+                if start.matches(name) {
+                    "color = green".to_string()
+                } else if goal.matches(name) {
+                    "color = red".to_string()
+                } else {
+                    String::new()
+                }
+            })
+        );
+        dot.replacen(
+            "{\n",
+            &format!(
+                "{{\n    label = \"instructions {} ({} steps per cycle)\"\n",
+                self.instructions.iter().collect::<String>(),
+                instruction_length
+            ),
+            1,
+        )
+    }
+
+    // The node reached from `node` by the step-th move
+    pub fn step(&self, node: usize, step: u64) -> usize {
+        match self.instructions[(step % self.instructions.len() as u64) as usize] {
+            'L' => self.left[node],
+            _ => self.right[node],
+        }
+    }
+}
@@ -1,66 +1,179 @@
+mod polynomial;
+
+use crate::polynomial::Polynomial;
+use num_bigint::BigInt;
+use std::fmt;
+
 const EXAMPLE: &str = include_str!("example.txt");
 const INPUT: &str = include_str!("input.txt");
 
-fn parse_start_values(line: &str) -> Vec<i64> {
-    line.split_ascii_whitespace()
-        .map(|w| w.parse::<i64>())
-        .filter_map(Result::ok)
-        .collect::<Vec<i64>>()
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ReadingPolicy {
+    Error,
+    // The interpolating polynomial treats the single value of the last row as a constant
+    ExtrapolateWithLastNonZeroRow,
+    Skip,
+}
+
+// Lines are numbered from 1
+#[derive(Debug, Clone, PartialEq)]
+enum ReadingError {
+    InvalidToken { line: usize, token: String },
+    Empty { line: usize },
+    OutOfDifferences { line: usize },
 }
 
-fn generate_pyramids(start_values: Vec<i64>) -> Vec<Vec<i64>> {
-    let mut pyramid: Vec<Vec<i64>> = Vec::new();
-    pyramid.push(start_values);
-    while !pyramid.last().unwrap().iter().all(|v| *v == 0) {
-        let original_vec = pyramid.last().unwrap();
-        let differences: Vec<_> = original_vec
-            .iter()
-            .zip(original_vec.iter().skip(1))
-            .map(|(a, b)| b - a)
-            .collect();
-        pyramid.push(differences);
+impl fmt::Display for ReadingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadingError::InvalidToken { line, token } => {
+                write!(f, "line {}: invalid token {:?} in reading", line, token)
+            }
+            ReadingError::Empty { line } => write!(f, "line {}: empty reading", line),
+            ReadingError::OutOfDifferences { line } => write!(
+                f,
+                "line {}: reading ran out of differences before reaching zeros",
+                line
+            ),
+        }
     }
-    pyramid
 }
 
-fn star_one(input: &str) -> i64 {
-    let mut sum = 0;
-    for line in input.lines() {
-        let start_values = parse_start_values(line);
-        let pyramid = generate_pyramids(start_values);
-        sum += pyramid
-            .iter()
-            .map(|inner_vec| *inner_vec.last().unwrap())
-            .collect::<Vec<i64>>()
-            .iter()
-            .sum::<i64>();
+// The first token that is not a number is the error
+fn parse_start_values(line: &str) -> Result<Vec<i64>, &str> {
+    line.split_ascii_whitespace()
+        .map(|w| w.parse::<i64>().map_err(|_| w))
+        .collect()
+}
+
+fn fit_reading(
+    line_number: usize,
+    line: &str,
+    policy: ReadingPolicy,
+) -> Result<Option<Polynomial>, ReadingError> {
+    let start_values = match (parse_start_values(line), policy) {
+        (Ok(start_values), _) => start_values,
+        (Err(_), ReadingPolicy::Skip) => return Ok(None),
+        (Err(token), _) => {
+            return Err(ReadingError::InvalidToken {
+                line: line_number,
+                token: token.to_string(),
+            })
+        }
+    };
+    if start_values.is_empty() {
+        return match policy {
+            ReadingPolicy::Skip => Ok(None),
+            _ => Err(ReadingError::Empty { line: line_number }),
+        };
+    }
+    let polynomial = Polynomial::fit(&start_values);
+    if polynomial.reached_zero_row {
+        return Ok(Some(polynomial));
+    }
+    match policy {
+        ReadingPolicy::Error => Err(ReadingError::OutOfDifferences { line: line_number }),
+        ReadingPolicy::ExtrapolateWithLastNonZeroRow => Ok(Some(polynomial)),
+        ReadingPolicy::Skip => Ok(None),
     }
-    return sum;
 }
 
-fn star_two(input: &str) -> i64 {
-    let mut sum = 0;
-    for line in input.lines() {
-        let start_values = parse_start_values(line);
-        let pyramid = generate_pyramids(start_values);
-        sum += pyramid
-            .iter()
-            .map(|inner_vec| *inner_vec.first().unwrap())
-            .collect::<Vec<i64>>()
-            .iter()
-            .enumerate()
-            .map(|(index, &value)| if index % 2 == 1 { -value } else { value })
-            .collect::<Vec<i64>>()
-            .iter()
-            .sum::<i64>();
+fn extrapolate(
+    input: &str,
+    policy: ReadingPolicy,
+    index: impl Fn(&Polynomial) -> i64,
+) -> Result<BigInt, ReadingError> {
+    let mut sum = BigInt::from(0);
+    for (line_index, line) in input.lines().enumerate() {
+        if let Some(polynomial) = fit_reading(line_index + 1, line, policy)? {
+            sum += polynomial.evaluate(index(&polynomial));
+        }
     }
-    return sum;
+    Ok(sum)
+}
+
+fn star_one(input: &str, policy: ReadingPolicy) -> Result<BigInt, ReadingError> {
+    extrapolate(input, policy, |polynomial| polynomial.samples as i64)
+}
+
+fn star_two(input: &str, policy: ReadingPolicy) -> Result<BigInt, ReadingError> {
+    extrapolate(input, policy, |_| -1)
 }
 
 fn main() {
-    println!("Example: star1 {}", star_one(EXAMPLE));
-    println!("Input: star1 {}", star_one(INPUT));
+    assert_eq!(
+        star_one(EXAMPLE, ReadingPolicy::Error).unwrap(),
+        BigInt::from(114)
+    );
+    assert_eq!(
+        star_two(EXAMPLE, ReadingPolicy::Error).unwrap(),
+        BigInt::from(2)
+    );
+
+    for line in EXAMPLE.lines() {
+        let polynomial = Polynomial::fit(&parse_start_values(line).unwrap());
+        println!(
+            "{}: degree {:?}, f(x) = {}, f(1000000) = {}, f(-1000000) = {}",
+            line,
+            polynomial.degree(),
+            polynomial,
+            polynomial.evaluate(1_000_000),
+            polynomial.evaluate(-1_000_000)
+        );
+    }
+
+    let triangular = Polynomial::fit(&[1, 3, 6, 10, 15, 21]);
+    assert!(triangular.reached_zero_row);
+    assert_eq!(triangular.degree(), Some(2));
+    assert_eq!(triangular.to_string(), "1/2x^2 + 3/2x + 1");
+    assert_eq!(
+        triangular.evaluate(i64::MAX),
+        (BigInt::from(i64::MAX) + 1) * (BigInt::from(i64::MAX) + 2) / 2
+    );
+    let powers_of_two = Polynomial::fit(&[1, 2, 4, 8, 16]);
+    assert!(!powers_of_two.reached_zero_row);
+
+    let unlucky = format!("{}\n1 2 4 8 16", EXAMPLE.trim_end());
+    let out_of_differences = star_one(&unlucky, ReadingPolicy::Error).unwrap_err();
+    assert_eq!(
+        out_of_differences,
+        ReadingError::OutOfDifferences { line: 4 }
+    );
+    println!("{}", out_of_differences);
+    assert_eq!(
+        star_one(&unlucky, ReadingPolicy::ExtrapolateWithLastNonZeroRow),
+        Ok(BigInt::from(114 + 31))
+    );
+    assert_eq!(
+        star_one(&unlucky, ReadingPolicy::Skip),
+        Ok(BigInt::from(114))
+    );
+    let malformed = format!("{}\n1 2 x 4\n", EXAMPLE.trim_end());
+    assert_eq!(
+        star_two(&malformed, ReadingPolicy::Error),
+        Err(ReadingError::InvalidToken {
+            line: 4,
+            token: "x".to_string()
+        })
+    );
+    assert_eq!(
+        star_two(&malformed, ReadingPolicy::ExtrapolateWithLastNonZeroRow),
+        Err(ReadingError::InvalidToken {
+            line: 4,
+            token: "x".to_string()
+        })
+    );
+    assert_eq!(
+        star_two(&malformed, ReadingPolicy::Skip),
+        Ok(BigInt::from(2))
+    );
 
-    println!("Example: star2 {}", star_two(EXAMPLE));
-    println!("Input: star2 {}", star_two(INPUT));
+    println!(
+        "Input: star1 {}",
+        star_one(INPUT, ReadingPolicy::Error).unwrap()
+    );
+    println!(
+        "Input: star2 {}",
+        star_two(INPUT, ReadingPolicy::Error).unwrap()
+    );
 }
@@ -0,0 +1,114 @@
+use std::fmt;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+// Newton forward difference form: f(x) = sum of newton[k] * binomial(x, k)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial {
+    pub newton: Vec<BigInt>,
+    // Whether the difference table reached an all-zero row inside the sample,
+    // without it the sequence is only interpolated and not proven to be polynomial
+    pub reached_zero_row: bool,
+    pub samples: usize,
+}
+
+impl Polynomial {
+    pub fn fit(values: &[i64]) -> Self {
+        let mut newton = Vec::new();
+        let mut row: Vec<BigInt> = values.iter().map(|value| BigInt::from(*value)).collect();
+        let mut reached_zero_row = false;
+        while !row.is_empty() {
+            if row.iter().all(|value| value.is_zero()) {
+                reached_zero_row = true;
+                break;
+            }
+            newton.push(row[0].clone());
+            row = row
+                .iter()
+                .zip(row.iter().skip(1))
+                .map(|(a, b)| b - a)
+                .collect();
+        }
+        Self {
+            newton,
+            reached_zero_row,
+            samples: values.len(),
+        }
+    }
+
+    // None for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.newton.iter().rposition(|value| !value.is_zero())
+    }
+
+    // Exact for every integer index, negative ones included
+    pub fn evaluate(&self, index: i64) -> BigInt {
+        let index = BigInt::from(index);
+        let mut binomial = BigInt::one();
+        let mut result = BigInt::zero();
+        for (k, difference) in self.newton.iter().enumerate() {
+            if k > 0 {
+                binomial = binomial * (&index - (k - 1)) / k;
+            }
+            result += difference * &binomial;
+        }
+        result
+    }
+
+    // Coefficients of x^0, x^1, ... in the usual monomial form
+    pub fn coefficients(&self) -> Vec<BigRational> {
+        let mut coefficients = vec![BigRational::zero(); self.newton.len()];
+        // x * (x - 1) * ... * (x - k + 1) / k! in the monomial form
+        let mut falling: Vec<BigRational> = vec![BigRational::one()];
+        for (k, difference) in self.newton.iter().enumerate() {
+            if k > 0 {
+                let shift = BigRational::from_integer(BigInt::from(k - 1));
+                let divisor = BigRational::from_integer(BigInt::from(k));
+                let mut next = vec![BigRational::zero(); falling.len() + 1];
+                for (power, coefficient) in falling.iter().enumerate() {
+                    next[power + 1] += coefficient / &divisor;
+                    next[power] -= coefficient * &shift / &divisor;
+                }
+                falling = next;
+            }
+            let difference = BigRational::from_integer(difference.clone());
+            for (power, coefficient) in falling.iter().enumerate() {
+                coefficients[power] += coefficient * &difference;
+            }
+        }
+        coefficients.truncate(self.degree().map_or(0, |degree| degree + 1));
+        coefficients
+    }
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<String> = self
+            .coefficients()
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, coefficient)| !coefficient.is_zero())
+            .map(|(power, coefficient)| {
+                let factor = if power == 0 || !coefficient.abs().is_one() {
+                    coefficient.to_string()
+                } else if coefficient.is_negative() {
+                    "-".to_string()
+                } else {
+                    String::new()
+                };
+                match power {
+                    0 => factor,
+                    1 => format!("{}x", factor),
+                    _ => format!("{}x^{}", factor, power),
+                }
+            })
+            .collect();
+        if terms.is_empty() {
+            return write!(f, "0");
+        }
+        write!(f, "{}", terms.join(" + ").replace("+ -", "- "))
+    }
+}